@@ -1,7 +1,13 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::env::Args;
 use std::str::FromStr;
 
+use crate::image::{
+    parse_component, Bilateral, Canny, Channel, ChannelMixer, ChannelSource, Clahe, ColorSpace, Connectivity, Diffusion, Dither,
+    DitherMethod, Duotone, EdgeDetector, Emboss, Equalize, GradientOutput, GrayWeights, MorphOp, Operator, Paint, PaletteMethod,
+    PaletteSource, Quantize, RankFilter, Shape, Stretch, Threshold, ToneCurve, Unsharp, WhiteBalance, Window,
+};
+
 
 #[cfg(test)]
 mod tests;
@@ -14,11 +20,80 @@ mod tests;
 pub enum ManipOption {
     DoNothing,
     Negate,
-    Brighten(usize),
+    Brighten(i32),
     Sharpen,
     Smooth,
     Grayscale,
+    /// Grayscale, with the given weights rather than the README's
+    WeightedGrayscale(GrayWeights),
     Contrast,
+    /// Convolve with a kernel read from the named file
+    Convolve(String),
+    /// Gaussian blur, optionally approximated with repeated box blurs
+    GaussianBlur { sigma: f64, fast: bool },
+    UnsharpMask(Unsharp),
+    RankFilter(RankFilter),
+    Bilateral(Bilateral),
+    Edges(EdgeDetector),
+    Canny(Canny),
+    Equalize(Equalize),
+    /// Percentile-clipped contrast stretch which (unlike `Contrast`) keeps color
+    Stretch(Stretch),
+    /// Gamma, levels or curves adjustment
+    Tone(ToneCurve),
+    /// Shrink by an integer factor, averaging blocks of pixels
+    Downscale(usize),
+    /// Apply the inner option in linear light rather than to gamma-encoded values
+    Linear(Box<ManipOption>),
+    /// Rotate hues by a number of degrees
+    Hue(f64),
+    /// Scale saturation by a factor
+    Saturation(f64),
+    /// Boost (or cut) saturation, mostly in muted colors
+    Vibrance(f64),
+    WhiteBalance(WhiteBalance),
+    /// Extract one channel as a grayscale image
+    Channel(Channel),
+    /// Rearrange channels, giving the source of red, green and blue in turn
+    Permute([Channel; 3]),
+    /// Mix channels with a 3x3 matrix
+    Mix(ChannelMixer),
+    /// Build a color image from three channels, each from the input or another file
+    Combine([ChannelSource; 3]),
+    /// Binarize into a bilevel image
+    Threshold(Threshold),
+    /// Reduce to a new maxval, dithering to hide the banding
+    Dither(Dither),
+    /// Reduce to a palette of colors
+    Quantize(Quantize),
+    /// Choose a palette of colors, producing it as an image with one pixel per color
+    Palette(PaletteMethod),
+    /// Reduce every channel to a number of evenly spaced levels
+    Posterize(usize),
+    /// Invert values above a threshold (a fraction of maxval)
+    Solarize(f64),
+    Sepia,
+    /// Map luminance onto the range between two colors
+    Duotone(Duotone),
+    /// Replace blocks of pixels with their average
+    Pixelate(usize),
+    Emboss(Emboss),
+    /// Kuwahara or oil paint filter
+    Paint(Paint),
+    /// Pencil sketch, blurring with the given sigma
+    Sketch(f64),
+    /// Erode, dilate, etc. with a structuring element
+    Morphology { op: MorphOp, shape: Shape },
+    /// Paint each connected component of the foreground a different color
+    Label(Connectivity),
+    /// Distance from the foreground to the background, scaled to the given maxval
+    Distance(usize),
+    /// Thin the foreground down to a one-pixel-wide skeleton
+    Skeleton,
+    /// Extract one component of a color space as a grayscale image
+    Component { space: ColorSpace, index: usize },
+    /// Apply the inner option to one component of a color space, leaving the others alone
+    InComponent { space: ColorSpace, index: usize, op: Box<ManipOption> },
 }
 
 /// Output mode for the image written out
@@ -26,20 +101,89 @@ pub enum ManipOption {
 pub enum OutputMode {
     Ascii,
     Binary,
+    /// Bilevel images only, as a plain (P1) PBM
+    AsciiBitmap,
+    /// Bilevel images only, as a raw (P4) PBM
+    BinaryBitmap,
+    /// Print the image's histogram and statistics instead of writing it
+    Stats(StatsFormat),
+    /// Print the image's connected components instead of writing it
+    Blobs(BlobFormat),
+    /// Write the distance transform of the image as a PFM
+    Distances,
+}
+
+/// How to print an image's statistics
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct StatsFormat {
+    pub json: bool,
+    /// Number of histogram bins, or `None` for one bin per value
+    pub bins: Option<usize>,
+}
+
+impl FromStr for StatsFormat {
+    type Err = String;
+
+    /// Parses `text` or `json`, optionally followed by `,bins`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let usage = || format!("Expected 'text|json[,bins]' (got '{}')", s);
+        let mut parts = s.splitn(2, ',');
+        let json = match parts.next() {
+            Some("text") => false,
+            Some("json") => true,
+            _ => return Err(usage()),
+        };
+        let bins = match parts.next().map(|b| b.parse::<usize>()) {
+            None => None,
+            Some(Ok(b)) if b > 0 => Some(b),
+            Some(_) => return Err(usage()),
+        };
+        Ok(StatsFormat { json, bins })
+    }
+}
+
+/// How to print an image's connected components
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct BlobFormat {
+    pub json: bool,
+    pub connectivity: Connectivity,
+}
+
+impl FromStr for BlobFormat {
+    type Err = String;
+
+    /// Parses `text` or `json`, optionally followed by `,4` or `,8` (the default)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let usage = || format!("Expected 'text|json[,4|8]' (got '{}')", s);
+        let mut parts = s.splitn(2, ',');
+        let json = match parts.next() {
+            Some("text") => false,
+            Some("json") => true,
+            _ => return Err(usage()),
+        };
+        let connectivity = match parts.next() {
+            None => Connectivity::Eight,
+            Some(c) => c.parse()?,
+        };
+        Ok(BlobFormat { json, connectivity })
+    }
 }
 
 /// `ProgOpts` contain the runtime options for a single invocation of photomanip
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct ProgOpts {
-    op: ManipOption,
-    mode: OutputMode,
-    infile: String,
-    outfile: String,
+    pub op: ManipOption,
+    pub mode: OutputMode,
+    pub infile: String,
+    pub outfile: String,
 }
 
 impl ProgOpts {
     pub fn from_env() -> Result<ProgOpts, String> {
-        ProgOpts::parse(std::env::args())
+        // skip the program name
+        ProgOpts::parse(std::env::args().skip(1))
     }
 
     /// Parses the given command line `args` into explicit program options
@@ -49,6 +193,31 @@ impl ProgOpts {
     {
         let args: Vec<String> = args.collect();
 
+        if args.first().map(String::as_str) == Some("--linear") {
+            let mut opts = ProgOpts::parse(args.into_iter().skip(1))?;
+            match opts.op {
+                ManipOption::Linear(_) => return Err("'--linear' may only be given once".to_string()),
+                ManipOption::InComponent { .. } => {
+                    return Err("'--linear' can't be combined with '--in'".to_string())
+                }
+                _ => (),
+            }
+            opts.op = ManipOption::Linear(Box::new(opts.op));
+            return Ok(opts);
+        }
+
+        if args.first().map(String::as_str) == Some("--in") {
+            let (space, index) = parse_component(args.get(1).map(String::as_str).unwrap_or(""))?;
+            let mut opts = ProgOpts::parse(args.into_iter().skip(2))?;
+            match opts.op {
+                ManipOption::InComponent { .. } => return Err("'--in' may only be given once".to_string()),
+                ManipOption::Linear(_) => return Err("'--linear' can't be combined with '--in'".to_string()),
+                _ => (),
+            }
+            opts.op = ManipOption::InComponent { space, index, op: Box::new(opts.op) };
+            return Ok(opts);
+        }
+
         let op = match args.len() {
             3 => ManipOption::DoNothing,
             4 => match args[0].as_str() {
@@ -57,11 +226,168 @@ impl ProgOpts {
                 "-n" => ManipOption::Negate,
                 "-p" => ManipOption::Sharpen,
                 "-s" => ManipOption::Smooth,
+                "-y" => ManipOption::Sepia,
+                "-z" => ManipOption::Skeleton,
                 other => return Err(format!("Unexpected option '{}'", other)),
             },
             5 => match args[0].as_str() {
-                "-b" => ManipOption::Brighten(usize::from_str_radix(&args[1], 10).unwrap()),
-                _ => return Err(String::new()),
+                "-a" => {
+                    let (list, luminance) = match args[1].strip_suffix(",luma") {
+                        Some(list) => (list, true),
+                        None => (args[1].as_str(), false),
+                    };
+                    match *numbers(list)?.as_slice() {
+                        [low, high] if 0.0 <= low && low < high && high <= 100.0 => {
+                            ManipOption::Stretch(Stretch { low, high, luminance })
+                        }
+                        _ => return Err(format!(
+                            "Expected percentiles 'low,high' with 0 <= low < high <= 100 (got '{}')",
+                            args[1]
+                        )),
+                    }
+                }
+                "-b" => match args[1].parse::<i32>() {
+                    Ok(amount) if (-255..=255).contains(&amount) => ManipOption::Brighten(amount),
+                    _ => return Err(format!("Invalid brighten amount '{}'", args[1])),
+                },
+                "-k" => ManipOption::Convolve(args[1].clone()),
+                "-G" => {
+                    let (sigma, fast) = match args[1].strip_suffix(",fast") {
+                        Some(sigma) => (sigma, true),
+                        None => (args[1].as_str(), false),
+                    };
+                    ManipOption::GaussianBlur { sigma: positive(sigma)?, fast }
+                }
+                "-t" => ManipOption::Tone(tone_curve(&args[1])?),
+                "-w" => ManipOption::WeightedGrayscale(args[1].parse()?),
+                "-u" => {
+                    let (list, luminance) = match args[1].strip_suffix(",luma") {
+                        Some(list) => (list, true),
+                        None => (args[1].as_str(), false),
+                    };
+                    match numbers(list)?.as_slice() {
+                        &[radius, amount, threshold] if radius > 0.0 && threshold >= 0.0 => {
+                            ManipOption::UnsharpMask(Unsharp { radius, amount, threshold, luminance })
+                        }
+                        _ => return Err(format!(
+                            "Expected 'radius,amount,threshold' for unsharp mask (got '{}')",
+                            args[1]
+                        )),
+                    }
+                }
+                "-r" => ManipOption::RankFilter(rank_filter(&args[1])?),
+                "-e" => ManipOption::Edges(edge_detector(&args[1])?),
+                "-C" => match *numbers(&args[1])?.as_slice() {
+                    [sigma] if sigma > 0.0 => ManipOption::Canny(Canny { sigma, thresholds: None }),
+                    [sigma, low, high] if sigma > 0.0 && 0.0 <= low && low <= high && high <= 1.0 => {
+                        ManipOption::Canny(Canny { sigma, thresholds: Some((low, high)) })
+                    }
+                    _ => return Err(format!(
+                        "Expected 'sigma[,low,high]' with 0 <= low <= high <= 1 (got '{}')",
+                        args[1]
+                    )),
+                },
+                "-d" => match args[1].parse::<usize>() {
+                    Ok(factor) if factor > 0 => ManipOption::Downscale(factor),
+                    _ => return Err(format!("Invalid downscale factor '{}'", args[1])),
+                },
+                "-H" => match args[1].parse::<f64>() {
+                    Ok(degrees) if degrees.is_finite() => ManipOption::Hue(degrees),
+                    _ => return Err(format!("Invalid hue rotation '{}'", args[1])),
+                },
+                "-S" => match args[1].parse::<f64>() {
+                    Ok(factor) if factor >= 0.0 && factor.is_finite() => ManipOption::Saturation(factor),
+                    _ => return Err(format!("Expected a non-negative saturation factor (got '{}')", args[1])),
+                },
+                "-V" => match args[1].parse::<f64>() {
+                    Ok(amount) if (-1.0..=1.0).contains(&amount) => ManipOption::Vibrance(amount),
+                    _ => return Err(format!("Expected a vibrance from -1 to 1 (got '{}')", args[1])),
+                },
+                "-B" => ManipOption::WhiteBalance(white_balance(&args[1])?),
+                "-X" => ManipOption::Channel(args[1].parse()?),
+                "-P" => {
+                    let order: Vec<Channel> = args[1].chars()
+                        .map(|c| c.to_string().parse())
+                        .collect::<Result<_, _>>()?;
+                    match *order.as_slice() {
+                        [r, g, b] => ManipOption::Permute([r, g, b]),
+                        _ => return Err(format!("Expected three channels, e.g. 'bgr' (got '{}')", args[1])),
+                    }
+                }
+                "-m" => match *numbers(&args[1])?.as_slice() {
+                    [rr, rg, rb, gr, gg, gb, br, bg, bb] => {
+                        ManipOption::Mix(ChannelMixer([[rr, rg, rb], [gr, gg, gb], [br, bg, bb]]))
+                    }
+                    _ => return Err(format!("Expected nine comma-separated weights (got '{}')", args[1])),
+                },
+                "-J" => {
+                    let sources: Vec<ChannelSource> = args[1].split(',')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?;
+                    match <[ChannelSource; 3]>::try_from(sources) {
+                        Ok(sources) => ManipOption::Combine(sources),
+                        Err(_) => return Err(format!(
+                            "Expected three comma-separated channels or files (got '{}')",
+                            args[1]
+                        )),
+                    }
+                }
+                "-T" => ManipOption::Threshold(threshold(&args[1])?),
+                "-D" => ManipOption::Dither(dither(&args[1])?),
+                "-Q" => ManipOption::Quantize(quantize(&args[1])?),
+                "-K" => match palette_method(&args[1])? {
+                    (method, rest) if rest.is_empty() => ManipOption::Palette(method),
+                    _ => return Err(format!("Expected 'mediancut|kmeans,colors' (got '{}')", args[1])),
+                },
+                "-q" => match args[1].parse::<usize>() {
+                    Ok(levels) if levels >= 2 => ManipOption::Posterize(levels),
+                    _ => return Err(format!("Expected at least 2 posterize levels (got '{}')", args[1])),
+                },
+                "-Z" => match args[1].parse::<f64>() {
+                    Ok(t) if (0.0..=1.0).contains(&t) => ManipOption::Solarize(t),
+                    _ => return Err(format!("Expected a solarize threshold from 0 to 1 (got '{}')", args[1])),
+                },
+                "-Y" => match args[1].split_once(',') {
+                    Some((dark, light)) => {
+                        ManipOption::Duotone(Duotone { dark: hex_color(dark)?, light: hex_color(light)? })
+                    }
+                    None => return Err(format!("Expected two colors 'dark,light' (got '{}')", args[1])),
+                },
+                "-M" => match args[1].parse::<usize>() {
+                    Ok(block) if block > 0 => ManipOption::Pixelate(block),
+                    _ => return Err(format!("Invalid pixelate block size '{}'", args[1])),
+                },
+                "-R" => match *numbers(&args[1])?.as_slice() {
+                    [angle] => ManipOption::Emboss(Emboss { angle, depth: 1.0 }),
+                    [angle, depth] if depth > 0.0 => ManipOption::Emboss(Emboss { angle, depth }),
+                    _ => return Err(format!("Expected 'angle[,depth]' for emboss (got '{}')", args[1])),
+                },
+                "-O" => ManipOption::Paint(paint(&args[1])?),
+                "-I" => ManipOption::Sketch(positive(&args[1])?),
+                "-L" => ManipOption::Label(args[1].parse()?),
+                "-F" => match args[1].parse::<usize>() {
+                    Ok(maxval) if (1..=65535).contains(&maxval) => ManipOption::Distance(maxval),
+                    _ => return Err(format!("Expected a maxval from 1 to 65535 (got '{}')", args[1])),
+                },
+                "-f" => {
+                    let (op, shape) = morphology(&args[1])?;
+                    ManipOption::Morphology { op, shape }
+                }
+                "-x" => {
+                    let (space, index) = parse_component(&args[1])?;
+                    ManipOption::Component { space, index }
+                }
+                "-E" => ManipOption::Equalize(equalize(&args[1])?),
+                "-l" => match numbers(&args[1])?.as_slice() {
+                    &[spatial, range] if spatial > 0.0 && range > 0.0 => {
+                        ManipOption::Bilateral(Bilateral { spatial, range })
+                    }
+                    _ => return Err(format!(
+                        "Expected positive 'spatial,range' for bilateral filter (got '{}')",
+                        args[1]
+                    )),
+                },
+                other => return Err(format!("Unexpected option '{}'", other)),
             },
             _ => return Err(String::new()),
         };
@@ -69,18 +395,363 @@ impl ProgOpts {
         let mode = match args[args.len() - 3].as_str() {
             "-oa" => OutputMode::Ascii,
             "-ob" => OutputMode::Binary,
+            "-oap" => OutputMode::AsciiBitmap,
+            "-obp" => OutputMode::BinaryBitmap,
+            "--distance" => OutputMode::Distances,
+            "--stats" | "--blobs" => {
+                // there's no output file to write, so the format takes its place
+                let format = &args[args.len() - 2];
+                let mode = if args[args.len() - 3] == "--stats" {
+                    OutputMode::Stats(format.parse()?)
+                } else {
+                    OutputMode::Blobs(format.parse()?)
+                };
+                return Ok(ProgOpts {
+                    op,
+                    mode,
+                    infile: args[args.len() - 1].clone(),
+                    outfile: String::new(),
+                });
+            }
             _ => return Err(String::new()),
         };
 
         Ok(ProgOpts {
             op,
             mode,
-            infile: args[args.len() - 2].clone(),
-            outfile: args[args.len() - 1].clone(),
+            infile: args[args.len() - 1].clone(),
+            outfile: args[args.len() - 2].clone(),
         })
     }
 }
 
+/// Parses a strictly positive, finite number from an option's argument
+fn positive(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(v) if v > 0.0 && v.is_finite() => Ok(v),
+        _ => Err(format!("Expected a positive number (got '{}')", arg)),
+    }
+}
+
+/// Parses a rank filter's argument: `rank,radius[,circle]`
+///
+/// The rank is one of `median`, `min`, `max`, or a percentile from 0 to 100.
+fn rank_filter(arg: &str) -> Result<RankFilter, String> {
+    let usage = || format!("Expected 'median|min|max|percentile,radius[,circle]' (got '{}')", arg);
+    let parts: Vec<&str> = arg.split(',').collect();
+
+    let percentile = match parts[0] {
+        "median" => 50.0,
+        "min" => 0.0,
+        "max" => 100.0,
+        p => match p.parse::<f64>() {
+            Ok(p) if (0.0..=100.0).contains(&p) => p,
+            _ => return Err(usage()),
+        },
+    };
+    let radius = match parts.get(1).map(|r| r.parse::<usize>()) {
+        Some(Ok(r)) => r,
+        _ => return Err(usage()),
+    };
+    let window = match parts.get(2..) {
+        Some([]) => Window::Square,
+        Some(["circle"]) => Window::Circle,
+        _ => return Err(usage()),
+    };
+
+    Ok(RankFilter { radius, percentile, window })
+}
+
+/// Parses an edge detector's argument: `sobel|prewitt|scharr[,direction]` or `laplacian[,sigma]`
+fn edge_detector(arg: &str) -> Result<EdgeDetector, String> {
+    let parts: Vec<&str> = arg.split(',').collect();
+    let operator = match parts[0] {
+        "sobel" => Operator::Sobel,
+        "prewitt" => Operator::Prewitt,
+        "scharr" => Operator::Scharr,
+        "laplacian" => {
+            return match parts[1..] {
+                [] => Ok(EdgeDetector::Laplacian(0.0)),
+                [sigma] => Ok(EdgeDetector::Laplacian(positive(sigma)?)),
+                _ => Err(format!("Expected 'laplacian[,sigma]' (got '{}')", arg)),
+            };
+        }
+        other => return Err(format!("Unknown edge detector '{}'", other)),
+    };
+    match parts[1..] {
+        [] => Ok(EdgeDetector::Gradient(operator, GradientOutput::Magnitude)),
+        ["direction"] => Ok(EdgeDetector::Gradient(operator, GradientOutput::Direction)),
+        _ => Err(format!("Expected '{}[,direction]' (got '{}')", parts[0], arg)),
+    }
+}
+
+/// Parses a histogram equalization's argument: `global` or `clahe[,tiles_x,tiles_y,clip_limit]`
+fn equalize(arg: &str) -> Result<Equalize, String> {
+    let usage = || format!("Expected 'global' or 'clahe[,tiles_x,tiles_y,clip_limit]' (got '{}')", arg);
+    match arg.split_once(',') {
+        None if arg == "global" => Ok(Equalize::Global),
+        None if arg == "clahe" => Ok(Equalize::Adaptive(Clahe::default())),
+        Some(("clahe", params)) => match *numbers(params)?.as_slice() {
+            [tx, ty, clip_limit] if tx >= 1.0 && ty >= 1.0 && tx.fract() == 0.0 && ty.fract() == 0.0
+                && clip_limit >= 1.0 =>
+            {
+                Ok(Equalize::Adaptive(Clahe { tiles_x: tx as usize, tiles_y: ty as usize, clip_limit }))
+            }
+            _ => Err(usage()),
+        },
+        _ => Err(usage()),
+    }
+}
+
+/// Parses a tone curve's argument, one of:
+///
+/// - `gamma,g`
+/// - `levels,black,white,gamma,out_black,out_white`
+/// - `curve,x:y,x:y,...` or `spline,x:y,x:y,...`
+fn tone_curve(arg: &str) -> Result<ToneCurve, String> {
+    let fraction = |v: f64| (0.0..=1.0).contains(&v);
+    let (kind, params) = arg.split_once(',').unwrap_or((arg, ""));
+    match kind {
+        "gamma" => Ok(ToneCurve::Gamma(positive(params)?)),
+        "levels" => match *numbers(params)?.as_slice() {
+            [black, white, gamma, out_black, out_white]
+                if fraction(black) && fraction(white) && black < white && gamma > 0.0
+                    && fraction(out_black) && fraction(out_white) =>
+            {
+                Ok(ToneCurve::Levels { black, white, gamma, out_black, out_white })
+            }
+            _ => Err(format!(
+                "Expected 'levels,black,white,gamma,out_black,out_white' (got '{}')",
+                arg
+            )),
+        },
+        "curve" | "spline" => {
+            let mut points = params.split(',')
+                .map(|p| match p.split_once(':').map(|(x, y)| (x.parse::<f64>(), y.parse::<f64>())) {
+                    Some((Ok(x), Ok(y))) if fraction(x) && fraction(y) => Ok((x, y)),
+                    _ => Err(format!("Expected a control point 'x:y' from 0 to 1 (got '{}')", p)),
+                })
+                .collect::<Result<Vec<(f64, f64)>, String>>()?;
+            points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            if points.len() < 2 || points.windows(2).any(|w| w[0].0 == w[1].0) {
+                return Err(format!("Expected at least two points with distinct inputs (got '{}')", arg));
+            }
+            Ok(ToneCurve::curve(points, kind == "spline"))
+        }
+        _ => Err(format!("Unknown tone curve '{}'", kind)),
+    }
+}
+
+/// Parses a white balance's argument, one of:
+///
+/// - `grayworld`
+/// - `whitepatch`
+/// - `percentile,p`
+/// - `kelvin,temperature[,tint]`
+fn white_balance(arg: &str) -> Result<WhiteBalance, String> {
+    let (kind, params) = arg.split_once(',').unwrap_or((arg, ""));
+    match (kind, params) {
+        ("grayworld", "") => Ok(WhiteBalance::GrayWorld),
+        ("whitepatch", "") => Ok(WhiteBalance::WhitePatch),
+        ("percentile", p) => match *numbers(p)?.as_slice() {
+            [p] if p > 0.0 && p <= 100.0 => Ok(WhiteBalance::Percentile(p)),
+            _ => Err(format!("Expected a percentile from 0 to 100 (got '{}')", arg)),
+        },
+        ("kelvin", params) => {
+            let (kelvin, tint) = match *numbers(params)?.as_slice() {
+                [kelvin] => (kelvin, 0.0),
+                [kelvin, tint] => (kelvin, tint),
+                _ => return Err(format!("Expected 'kelvin,temperature[,tint]' (got '{}')", arg)),
+            };
+            if !(2000.0..=40000.0).contains(&kelvin) || !(-1.0..=1.0).contains(&tint) {
+                return Err(format!(
+                    "Expected a temperature from 2000 to 40000 and a tint from -1 to 1 (got '{}')",
+                    arg
+                ));
+            }
+            Ok(WhiteBalance::Temperature { kelvin, tint })
+        }
+        _ => Err(format!(
+            "Expected 'grayworld', 'whitepatch', 'percentile,p' or 'kelvin,temperature[,tint]' (got '{}')",
+            arg
+        )),
+    }
+}
+
+/// Parses a threshold's argument, one of:
+///
+/// - `fixed,t` (from 0 to 1)
+/// - `otsu`
+/// - `mean,radius[,offset]`
+/// - `sauvola,radius[,k]`
+fn threshold(arg: &str) -> Result<Threshold, String> {
+    let (kind, params) = arg.split_once(',').unwrap_or((arg, ""));
+    let radius = |r: f64| r >= 1.0 && r.fract() == 0.0;
+    match kind {
+        "fixed" => match *numbers(params)?.as_slice() {
+            [t] if (0.0..=1.0).contains(&t) => Ok(Threshold::Fixed(t)),
+            _ => Err(format!("Expected 'fixed,t' with 0 <= t <= 1 (got '{}')", arg)),
+        },
+        "otsu" if params.is_empty() => Ok(Threshold::Otsu),
+        "mean" => match *numbers(params)?.as_slice() {
+            [r] if radius(r) => Ok(Threshold::Mean { radius: r as usize, offset: 0.0 }),
+            [r, offset] if radius(r) && (-1.0..=1.0).contains(&offset) => {
+                Ok(Threshold::Mean { radius: r as usize, offset })
+            }
+            _ => Err(format!("Expected 'mean,radius[,offset]' (got '{}')", arg)),
+        },
+        "sauvola" => match *numbers(params)?.as_slice() {
+            [r] if radius(r) => Ok(Threshold::Sauvola { radius: r as usize, k: 0.5 }),
+            [r, k] if radius(r) && k > 0.0 => Ok(Threshold::Sauvola { radius: r as usize, k }),
+            _ => Err(format!("Expected 'sauvola,radius[,k]' (got '{}')", arg)),
+        },
+        _ => Err(format!(
+            "Expected 'fixed,t', 'otsu', 'mean,radius[,offset]' or 'sauvola,radius[,k]' (got '{}')",
+            arg
+        )),
+    }
+}
+
+/// Parses a dither's argument: `method,maxval[,serpentine]`
+///
+/// The method is one of `fs`, `atkinson`, `jjn`, `bayer2`, `bayer4` or `bayer8`.
+fn dither(arg: &str) -> Result<Dither, String> {
+    let usage = || format!("Expected 'fs|atkinson|jjn|bayer2|bayer4|bayer8,maxval[,serpentine]' (got '{}')", arg);
+    let parts: Vec<&str> = arg.split(',').collect();
+    let maxval = match parts.get(1).map(|m| m.parse::<usize>()) {
+        Some(Ok(m)) if (1..=65535).contains(&m) => m,
+        _ => return Err(usage()),
+    };
+    let serpentine = match parts.get(2..) {
+        Some([]) => false,
+        Some(["serpentine"]) => true,
+        _ => return Err(usage()),
+    };
+    let kernel = match parts[0] {
+        "fs" => Diffusion::FloydSteinberg,
+        "atkinson" => Diffusion::Atkinson,
+        "jjn" => Diffusion::JarvisJudiceNinke,
+        bayer => {
+            let n = match bayer {
+                "bayer2" => 2,
+                "bayer4" => 4,
+                "bayer8" => 8,
+                _ => return Err(usage()),
+            };
+            if serpentine {
+                return Err(format!("Ordered dithering has no scanning order (got '{}')", arg));
+            }
+            return Ok(Dither { method: DitherMethod::Bayer(n), maxval });
+        }
+    };
+    Ok(Dither { method: DitherMethod::Diffuse { kernel, serpentine }, maxval })
+}
+
+/// Parses a generated palette, `mediancut,colors` or `kmeans,colors`, returning the rest of `arg`
+fn palette_method(arg: &str) -> Result<(PaletteMethod, Vec<&str>), String> {
+    let usage = || format!("Expected 'mediancut|kmeans,colors' (got '{}')", arg);
+    let parts: Vec<&str> = arg.split(',').collect();
+    let colors = match parts.get(1).map(|n| n.parse::<usize>()) {
+        Some(Ok(n)) if n > 0 => n,
+        _ => return Err(usage()),
+    };
+    let method = match parts[0] {
+        "mediancut" => PaletteMethod::MedianCut(colors),
+        "kmeans" => PaletteMethod::KMeans(colors),
+        _ => return Err(usage()),
+    };
+    Ok((method, parts[2..].to_vec()))
+}
+
+/// Parses a color quantization's argument: `palette[,dither[,serpentine]]`
+///
+/// The palette is either generated (see [`palette_method`]) or the path to a PPM, and the
+/// dither is one of `fs`, `atkinson` or `jjn`.
+fn quantize(arg: &str) -> Result<Quantize, String> {
+    let (palette, rest) = match arg.split(',').next() {
+        Some("mediancut") | Some("kmeans") => {
+            let (method, rest) = palette_method(arg)?;
+            (PaletteSource::Generate(method), rest)
+        }
+        Some(path) if !path.is_empty() => (PaletteSource::File(path.to_string()), arg.split(',').skip(1).collect()),
+        _ => return Err(format!("Expected a palette (got '{}')", arg)),
+    };
+    let dither = match rest.first() {
+        None => None,
+        Some(&"fs") => Some(Diffusion::FloydSteinberg),
+        Some(&"atkinson") => Some(Diffusion::Atkinson),
+        Some(&"jjn") => Some(Diffusion::JarvisJudiceNinke),
+        Some(other) => return Err(format!("Unknown dither '{}' (expected 'fs', 'atkinson' or 'jjn')", other)),
+    };
+    let serpentine = match rest.get(1..) {
+        None | Some([]) => false,
+        Some(["serpentine"]) => true,
+        Some(_) => return Err(format!("Expected 'palette[,dither[,serpentine]]' (got '{}')", arg)),
+    };
+    Ok(Quantize { palette, dither, serpentine })
+}
+
+/// Parses a color written as six hex digits, `rrggbb`, into fractions from 0 to 1
+fn hex_color(arg: &str) -> Result<[f64; 3], String> {
+    let channel = |i: usize| arg.get(i..i + 2).and_then(|h| u8::from_str_radix(h, 16).ok());
+    match (arg.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok([r, g, b].map(|c| c as f64 / 255.0)),
+        _ => Err(format!("Expected a color 'rrggbb' in hex (got '{}')", arg)),
+    }
+}
+
+/// Parses a painterly filter's argument: `kuwahara,radius` or `oil,radius,levels`
+fn paint(arg: &str) -> Result<Paint, String> {
+    let whole = |v: f64| v >= 1.0 && v.fract() == 0.0;
+    let (kind, params) = arg.split_once(',').unwrap_or((arg, ""));
+    match (kind, numbers(params)?.as_slice()) {
+        ("kuwahara", &[radius]) if whole(radius) => Ok(Paint::Kuwahara(radius as usize)),
+        ("oil", &[radius, levels]) if whole(radius) && whole(levels) => {
+            Ok(Paint::Oil { radius: radius as usize, levels: levels as usize })
+        }
+        _ => Err(format!("Expected 'kuwahara,radius' or 'oil,radius,levels' (got '{}')", arg)),
+    }
+}
+
+/// Parses a morphological operation's argument: `op,square|cross|disk,radius` or `op,file`
+fn morphology(arg: &str) -> Result<(MorphOp, Shape), String> {
+    let usage = || format!("Expected 'op,square|cross|disk,radius' or 'op,file' (got '{}')", arg);
+    let (op, element) = arg.split_once(',').ok_or_else(usage)?;
+    let op = match op {
+        "erode" => MorphOp::Erode,
+        "dilate" => MorphOp::Dilate,
+        "open" => MorphOp::Open,
+        "close" => MorphOp::Close,
+        "tophat" => MorphOp::TopHat,
+        "blackhat" => MorphOp::BlackHat,
+        "gradient" => MorphOp::Gradient,
+        other => return Err(format!("Unknown morphological operation '{}'", other)),
+    };
+
+    let (kind, radius) = element.split_once(',').unwrap_or((element, ""));
+    let shape: fn(usize) -> Shape = match kind {
+        "square" => Shape::Square,
+        "cross" => Shape::Cross,
+        "disk" => Shape::Disk,
+        "" => return Err(usage()),
+        _ => return Ok((op, Shape::File(element.to_string()))),
+    };
+    match radius.parse::<usize>() {
+        Ok(radius) => Ok((op, shape(radius))),
+        Err(_) => Err(usage()),
+    }
+}
+
+/// Parses a comma-separated list of numbers from an option's argument
+fn numbers(arg: &str) -> Result<Vec<f64>, String> {
+    arg.split(',')
+        .map(|v| v.parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("Expected a number (got '{}')", v)))
+        .collect()
+}
+
 impl TryInto<ProgOpts> for Args {
     type Error = String;
 
@@ -10,7 +10,7 @@ fn outbin() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-ob infile outfile".parse().unwrap();
+    let got: ProgOpts = "-ob outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -23,7 +23,7 @@ fn outascii() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-oa infile outfile".parse().unwrap();
+    let got: ProgOpts = "-oa outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -36,7 +36,7 @@ fn outfile() {
         outfile: String::from("test!"),
     };
 
-    let got: ProgOpts = "-oa infile test!".parse().unwrap();
+    let got: ProgOpts = "-oa test! infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -49,7 +49,7 @@ fn infile() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-oa tested! outfile".parse().unwrap();
+    let got: ProgOpts = "-oa outfile tested!".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -62,7 +62,7 @@ fn negate() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-n -oa infile outfile".parse().unwrap();
+    let got: ProgOpts = "-n -oa outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -75,13 +75,30 @@ fn brighten() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-b 24 -oa infile outfile".parse().unwrap();
+    let got: ProgOpts = "-b 24 -oa outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
+#[test]
+fn brighten_negative() {
+    let got: ProgOpts = "-b -24 -oa outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Brighten(-24));
+}
+
+#[test]
+fn brighten_nonnumeric() {
+    assert!("-b lots -oa outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn brighten_out_of_range() {
+    assert!("-b 256 -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-b -256 -oa outfile infile".parse::<ProgOpts>().is_err());
+}
+
 #[test]
 fn brighten_noarg() {
-    assert!("-b -oa infile outfile".parse::<ProgOpts>().is_err());
+    assert!("-b -oa outfile infile".parse::<ProgOpts>().is_err());
 }
 
 #[test]
@@ -93,7 +110,7 @@ fn contrast() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-c -oa infile outfile".parse().unwrap();
+    let got: ProgOpts = "-c -oa outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -106,7 +123,7 @@ fn grayscale() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-g -oa infile outfile".parse().unwrap();
+    let got: ProgOpts = "-g -oa outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -119,7 +136,7 @@ fn smooth() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-s -oa infile outfile".parse().unwrap();
+    let got: ProgOpts = "-s -oa outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
@@ -132,13 +149,252 @@ fn sharpen() {
         outfile: String::from("outfile"),
     };
 
-    let got: ProgOpts = "-p -oa infile outfile".parse().unwrap();
+    let got: ProgOpts = "-p -oa outfile infile".parse().unwrap();
+    assert_eq!(got, should_be);
+}
+
+#[test]
+fn convolve() {
+    let should_be = ProgOpts {
+        op: ManipOption::Convolve(String::from("edges.txt")),
+        mode: OutputMode::Binary,
+        infile: String::from("infile"),
+        outfile: String::from("outfile"),
+    };
+
+    let got: ProgOpts = "-k edges.txt -ob outfile infile".parse().unwrap();
     assert_eq!(got, should_be);
 }
 
+#[test]
+fn gaussian_blur() {
+    let got: ProgOpts = "-G 2.5 -oa outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::GaussianBlur { sigma: 2.5, fast: false });
+
+    let got: ProgOpts = "-G 12,fast -oa outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::GaussianBlur { sigma: 12.0, fast: true });
+}
+
+#[test]
+fn gaussian_blur_bad_sigma() {
+    assert!("-G -1 -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-G 0 -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-G 2,slow -oa outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn unsharp_mask() {
+    let got: ProgOpts = "-u 2,1.5,3 -oa outfile infile".parse().unwrap();
+    let params = Unsharp { radius: 2.0, amount: 1.5, threshold: 3.0, luminance: false };
+    assert_eq!(got.op, ManipOption::UnsharpMask(params));
+
+    let got: ProgOpts = "-u 2,1.5,3,luma -oa outfile infile".parse().unwrap();
+    let params = Unsharp { radius: 2.0, amount: 1.5, threshold: 3.0, luminance: true };
+    assert_eq!(got.op, ManipOption::UnsharpMask(params));
+}
+
+#[test]
+fn unsharp_mask_bad_args() {
+    assert!("-u 2,1.5 -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-u 0,1.5,3 -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-u 2,x,3 -oa outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn rank_filter() {
+    let got: ProgOpts = "-r median,2 -oa outfile infile".parse().unwrap();
+    let params = RankFilter { radius: 2, percentile: 50.0, window: Window::Square };
+    assert_eq!(got.op, ManipOption::RankFilter(params));
+
+    let got: ProgOpts = "-r 90,4,circle -oa outfile infile".parse().unwrap();
+    let params = RankFilter { radius: 4, percentile: 90.0, window: Window::Circle };
+    assert_eq!(got.op, ManipOption::RankFilter(params));
+
+    let got: ProgOpts = "-r max,1 -oa outfile infile".parse().unwrap();
+    let params = RankFilter { radius: 1, percentile: 100.0, window: Window::Square };
+    assert_eq!(got.op, ManipOption::RankFilter(params));
+}
+
+#[test]
+fn rank_filter_bad_args() {
+    assert!("-r median -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-r 101,2 -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-r mode,2 -oa outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-r min,2,diamond -oa outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn bilateral() {
+    let got: ProgOpts = "-l 3,0.1 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Bilateral(Bilateral { spatial: 3.0, range: 0.1 }));
+    assert!("-l 3 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-l 3,0 -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn edges() {
+    let got: ProgOpts = "-e sobel -ob outfile infile".parse().unwrap();
+    let detector = EdgeDetector::Gradient(Operator::Sobel, GradientOutput::Magnitude);
+    assert_eq!(got.op, ManipOption::Edges(detector));
+
+    let got: ProgOpts = "-e scharr,direction -ob outfile infile".parse().unwrap();
+    let detector = EdgeDetector::Gradient(Operator::Scharr, GradientOutput::Direction);
+    assert_eq!(got.op, ManipOption::Edges(detector));
+
+    let got: ProgOpts = "-e laplacian,1.4 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Edges(EdgeDetector::Laplacian(1.4)));
+}
+
+#[test]
+fn edges_bad_args() {
+    assert!("-e roberts -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-e sobel,1.4 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-e laplacian,direction -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn canny() {
+    let got: ProgOpts = "-C 1.4 -obp outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Canny(Canny { sigma: 1.4, thresholds: None }));
+    assert_eq!(got.mode, OutputMode::BinaryBitmap);
+
+    let got: ProgOpts = "-C 2,0.1,0.3 -oap outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Canny(Canny { sigma: 2.0, thresholds: Some((0.1, 0.3)) }));
+    assert_eq!(got.mode, OutputMode::AsciiBitmap);
+}
+
+#[test]
+fn canny_bad_args() {
+    assert!("-C 2,0.3,0.1 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-C 2,0.3 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-C 0 -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn stats() {
+    let should_be = ProgOpts {
+        op: ManipOption::DoNothing,
+        mode: OutputMode::Stats(StatsFormat { json: false, bins: None }),
+        infile: String::from("infile"),
+        outfile: String::new(),
+    };
+
+    let got: ProgOpts = "--stats text infile".parse().unwrap();
+    assert_eq!(got, should_be);
+
+    let got: ProgOpts = "-g --stats json,16 infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Grayscale);
+    assert_eq!(got.mode, OutputMode::Stats(StatsFormat { json: true, bins: Some(16) }));
+}
+
+#[test]
+fn stats_bad_format() {
+    assert!("--stats xml infile".parse::<ProgOpts>().is_err());
+    assert!("--stats json,0 infile".parse::<ProgOpts>().is_err());
+    assert!("--stats text,many infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn equalize() {
+    let got: ProgOpts = "-E global -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Equalize(Equalize::Global));
+
+    let got: ProgOpts = "-E clahe -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Equalize(Equalize::Adaptive(Clahe::default())));
+
+    let got: ProgOpts = "-E clahe,4,3,2.5 -ob outfile infile".parse().unwrap();
+    let clahe = Clahe { tiles_x: 4, tiles_y: 3, clip_limit: 2.5 };
+    assert_eq!(got.op, ManipOption::Equalize(Equalize::Adaptive(clahe)));
+}
+
+#[test]
+fn equalize_bad_args() {
+    assert!("-E local -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-E clahe,4,3 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-E clahe,0,3,2 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-E clahe,4,3,0.5 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-E global,1 -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn stretch() {
+    let got: ProgOpts = "-a 0.5,99.5 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Stretch(Stretch { low: 0.5, high: 99.5, luminance: false }));
+
+    let got: ProgOpts = "-a 1,99,luma -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Stretch(Stretch { low: 1.0, high: 99.0, luminance: true }));
+}
+
+#[test]
+fn stretch_bad_args() {
+    assert!("-a 99,1 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-a 1,101 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-a 1 -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn tone_curves() {
+    let got: ProgOpts = "-t gamma,2.2 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Tone(ToneCurve::Gamma(2.2)));
+
+    let got: ProgOpts = "-t levels,0.1,0.9,1.2,0,1 -ob outfile infile".parse().unwrap();
+    let levels = ToneCurve::Levels { black: 0.1, white: 0.9, gamma: 1.2, out_black: 0.0, out_white: 1.0 };
+    assert_eq!(got.op, ManipOption::Tone(levels));
+
+    // control points needn't be given in order
+    let got: ProgOpts = "-t spline,1:1,0:0,0.5:0.6 -ob outfile infile".parse().unwrap();
+    let curve = ToneCurve::curve(vec![(0.0, 0.0), (0.5, 0.6), (1.0, 1.0)], true);
+    assert_eq!(got.op, ManipOption::Tone(curve));
+}
+
+#[test]
+fn tone_curves_bad_args() {
+    assert!("-t gamma,-1 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-t levels,0.9,0.1,1,0,1 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-t curve,0.5:0.5 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-t curve,0:0,0:1 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-t curve,0:0,1:2 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-t sigmoid,1 -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn weighted_grayscale() {
+    let got: ProgOpts = "-w bt709 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::WeightedGrayscale(GrayWeights::BT709));
+    assert!("-w sepia -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn downscale() {
+    let got: ProgOpts = "-d 3 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Downscale(3));
+    assert!("-d 0 -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn linear() {
+    let should_be = ProgOpts {
+        op: ManipOption::Linear(Box::new(ManipOption::Smooth)),
+        mode: OutputMode::Ascii,
+        infile: String::from("infile"),
+        outfile: String::from("outfile"),
+    };
+
+    let got: ProgOpts = "--linear -s -oa outfile infile".parse().unwrap();
+    assert_eq!(got, should_be);
+
+    let got: ProgOpts = "--linear -d 2 -oa outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Linear(Box::new(ManipOption::Downscale(2))));
+}
+
+#[test]
+fn linear_twice() {
+    assert!("--linear --linear -s -oa outfile infile".parse::<ProgOpts>().is_err());
+}
+
 #[test]
 fn extra_contrast_arg() {
-    assert!("-c 69 -oa infile outfile".parse::<ProgOpts>().is_err());
+    assert!("-c 69 -oa outfile infile".parse::<ProgOpts>().is_err());
 }
 
 #[test]
@@ -160,3 +416,257 @@ fn no_args() {
 fn six_args() {
     assert!("1 2 3 4 5 6".parse::<ProgOpts>().is_err());
 }
+
+#[test]
+fn component() {
+    let got: ProgOpts = "-x hsv,s -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Component { space: ColorSpace::Hsv, index: 1 });
+    assert!("-x hsv,a -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-x rgb,r -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn in_component() {
+    let got: ProgOpts = "--in lab,l -E global -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::InComponent {
+        space: ColorSpace::Lab,
+        index: 0,
+        op: Box::new(ManipOption::Equalize(Equalize::Global)),
+    });
+    assert_eq!(got.infile, "infile");
+
+    // components are converted from gamma-encoded values, so linear light doesn't mix in
+    assert!("--linear --in ycbcr,y -s -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("--in ycbcr,y --linear -s -ob outfile infile".parse::<ProgOpts>().is_err());
+
+    assert!("--in lab,l --in hsv,v -s -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("--in lab -s -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn color_grading() {
+    let got: ProgOpts = "-H -45 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Hue(-45.0));
+    let got: ProgOpts = "-S 1.5 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Saturation(1.5));
+    let got: ProgOpts = "-V 0.5 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Vibrance(0.5));
+
+    assert!("-H red -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-S -1 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-V 2 -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn white_balance() {
+    let parse = |arg: &str| format!("-B {} -ob outfile infile", arg).parse::<ProgOpts>().map(|o| o.op);
+    assert_eq!(parse("grayworld"), Ok(ManipOption::WhiteBalance(WhiteBalance::GrayWorld)));
+    assert_eq!(parse("whitepatch"), Ok(ManipOption::WhiteBalance(WhiteBalance::WhitePatch)));
+    assert_eq!(parse("percentile,99.5"), Ok(ManipOption::WhiteBalance(WhiteBalance::Percentile(99.5))));
+    assert_eq!(
+        parse("kelvin,3200"),
+        Ok(ManipOption::WhiteBalance(WhiteBalance::Temperature { kelvin: 3200.0, tint: 0.0 }))
+    );
+    assert_eq!(
+        parse("kelvin,5000,-0.25"),
+        Ok(ManipOption::WhiteBalance(WhiteBalance::Temperature { kelvin: 5000.0, tint: -0.25 }))
+    );
+
+    assert!(parse("grayworld,1").is_err());
+    assert!(parse("percentile,0").is_err());
+    assert!(parse("kelvin,500").is_err());
+    assert!(parse("kelvin,5000,2").is_err());
+    assert!(parse("auto").is_err());
+}
+
+#[test]
+fn channels() {
+    let got: ProgOpts = "-X g -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Channel(Channel::Green));
+    let got: ProgOpts = "-P bgr -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Permute([Channel::Blue, Channel::Green, Channel::Red]));
+    let got: ProgOpts = "-m 0,0,1,0,1,0,1,0,0.5 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Mix(ChannelMixer([[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.5]])));
+    let got: ProgOpts = "-J r,nir.pgm,g -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Combine([
+        ChannelSource::Channel(Channel::Red),
+        ChannelSource::File("nir.pgm".to_string()),
+        ChannelSource::Channel(Channel::Green),
+    ]));
+
+    assert!("-X y -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-P rg -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-P rgba -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-m 1,0,0 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-J r,g -ob outfile infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn threshold() {
+    let parse = |arg: &str| format!("-T {} -obp outfile infile", arg).parse::<ProgOpts>().map(|o| o.op);
+    assert_eq!(parse("fixed,0.4"), Ok(ManipOption::Threshold(Threshold::Fixed(0.4))));
+    assert_eq!(parse("otsu"), Ok(ManipOption::Threshold(Threshold::Otsu)));
+    assert_eq!(parse("mean,7"), Ok(ManipOption::Threshold(Threshold::Mean { radius: 7, offset: 0.0 })));
+    assert_eq!(parse("mean,7,0.05"), Ok(ManipOption::Threshold(Threshold::Mean { radius: 7, offset: 0.05 })));
+    assert_eq!(parse("sauvola,15"), Ok(ManipOption::Threshold(Threshold::Sauvola { radius: 15, k: 0.5 })));
+    assert_eq!(parse("sauvola,15,0.2"), Ok(ManipOption::Threshold(Threshold::Sauvola { radius: 15, k: 0.2 })));
+
+    assert!(parse("fixed,2").is_err());
+    assert!(parse("otsu,1").is_err());
+    assert!(parse("mean,0").is_err());
+    assert!(parse("mean,2.5").is_err());
+    assert!(parse("sauvola,3,0").is_err());
+    assert!(parse("median,3").is_err());
+}
+
+#[test]
+fn dither() {
+    let parse = |arg: &str| format!("-D {} -ob outfile infile", arg).parse::<ProgOpts>().map(|o| o.op);
+    assert_eq!(parse("fs,1"), Ok(ManipOption::Dither(Dither {
+        method: DitherMethod::Diffuse { kernel: Diffusion::FloydSteinberg, serpentine: false },
+        maxval: 1,
+    })));
+    assert_eq!(parse("jjn,15,serpentine"), Ok(ManipOption::Dither(Dither {
+        method: DitherMethod::Diffuse { kernel: Diffusion::JarvisJudiceNinke, serpentine: true },
+        maxval: 15,
+    })));
+    assert_eq!(parse("bayer8,3"), Ok(ManipOption::Dither(Dither { method: DitherMethod::Bayer(8), maxval: 3 })));
+
+    assert!(parse("fs").is_err());
+    assert!(parse("fs,0").is_err());
+    assert!(parse("atkinson,1,zigzag").is_err());
+    assert!(parse("bayer4,1,serpentine").is_err());
+    assert!(parse("bayer3,1").is_err());
+}
+
+#[test]
+fn quantize() {
+    let parse = |arg: &str| format!("-Q {} -ob outfile infile", arg).parse::<ProgOpts>().map(|o| o.op);
+    assert_eq!(parse("mediancut,16"), Ok(ManipOption::Quantize(Quantize {
+        palette: PaletteSource::Generate(PaletteMethod::MedianCut(16)),
+        dither: None,
+        serpentine: false,
+    })));
+    assert_eq!(parse("kmeans,8,fs,serpentine"), Ok(ManipOption::Quantize(Quantize {
+        palette: PaletteSource::Generate(PaletteMethod::KMeans(8)),
+        dither: Some(Diffusion::FloydSteinberg),
+        serpentine: true,
+    })));
+    assert_eq!(parse("pal.ppm,atkinson"), Ok(ManipOption::Quantize(Quantize {
+        palette: PaletteSource::File("pal.ppm".to_string()),
+        dither: Some(Diffusion::Atkinson),
+        serpentine: false,
+    })));
+
+    assert!(parse("mediancut").is_err());
+    assert!(parse("kmeans,0").is_err());
+    assert!(parse("mediancut,16,bayer4").is_err());
+    assert!(parse("pal.ppm,fs,zigzag").is_err());
+}
+
+#[test]
+fn palette() {
+    let got: ProgOpts = "-K kmeans,16 -ob palette infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Palette(PaletteMethod::KMeans(16)));
+    assert!("-K kmeans,16,fs -ob palette infile".parse::<ProgOpts>().is_err());
+    assert!("-K pal.ppm -ob palette infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn effects() {
+    let parse = |args: &str| args.parse::<ProgOpts>().map(|o| o.op);
+    assert_eq!(parse("-q 4 -ob outfile infile"), Ok(ManipOption::Posterize(4)));
+    assert_eq!(parse("-Z 0.5 -ob outfile infile"), Ok(ManipOption::Solarize(0.5)));
+    assert_eq!(parse("-y -ob outfile infile"), Ok(ManipOption::Sepia));
+    assert_eq!(
+        parse("-Y 000080,ffcc00 -ob outfile infile"),
+        Ok(ManipOption::Duotone(Duotone { dark: [0.0, 0.0, 128.0 / 255.0], light: [1.0, 0.8, 0.0] }))
+    );
+    assert_eq!(parse("-M 8 -ob outfile infile"), Ok(ManipOption::Pixelate(8)));
+
+    assert!(parse("-q 1 -ob outfile infile").is_err());
+    assert!(parse("-Z 1.5 -ob outfile infile").is_err());
+    assert!(parse("-Y 000080 -ob outfile infile").is_err());
+    assert!(parse("-Y 00008g,ffffff -ob outfile infile").is_err());
+    assert!(parse("-Y #00008,ffffff -ob outfile infile").is_err());
+    assert!(parse("-M 0 -ob outfile infile").is_err());
+}
+
+#[test]
+fn stylize() {
+    let parse = |args: &str| args.parse::<ProgOpts>().map(|o| o.op);
+    assert_eq!(parse("-R 135 -ob outfile infile"), Ok(ManipOption::Emboss(Emboss { angle: 135.0, depth: 1.0 })));
+    assert_eq!(parse("-R -45,2 -ob outfile infile"), Ok(ManipOption::Emboss(Emboss { angle: -45.0, depth: 2.0 })));
+    assert_eq!(parse("-O kuwahara,3 -ob outfile infile"), Ok(ManipOption::Paint(Paint::Kuwahara(3))));
+    assert_eq!(
+        parse("-O oil,4,20 -ob outfile infile"),
+        Ok(ManipOption::Paint(Paint::Oil { radius: 4, levels: 20 }))
+    );
+    assert_eq!(parse("-I 5 -ob outfile infile"), Ok(ManipOption::Sketch(5.0)));
+
+    assert!(parse("-R 45,0 -ob outfile infile").is_err());
+    assert!(parse("-O kuwahara -ob outfile infile").is_err());
+    assert!(parse("-O oil,4 -ob outfile infile").is_err());
+    assert!(parse("-O oil,1.5,8 -ob outfile infile").is_err());
+    assert!(parse("-I 0 -ob outfile infile").is_err());
+}
+
+#[test]
+fn morphology() {
+    let parse = |args: &str| args.parse::<ProgOpts>().map(|o| o.op);
+    assert_eq!(
+        parse("-f erode,square,2 -ob outfile infile"),
+        Ok(ManipOption::Morphology { op: MorphOp::Erode, shape: Shape::Square(2) })
+    );
+    assert_eq!(
+        parse("-f tophat,disk,5 -ob outfile infile"),
+        Ok(ManipOption::Morphology { op: MorphOp::TopHat, shape: Shape::Disk(5) })
+    );
+    assert_eq!(
+        parse("-f gradient,cross,1 -ob outfile infile"),
+        Ok(ManipOption::Morphology { op: MorphOp::Gradient, shape: Shape::Cross(1) })
+    );
+    assert_eq!(
+        parse("-f close,brush.pbm -ob outfile infile"),
+        Ok(ManipOption::Morphology { op: MorphOp::Close, shape: Shape::File("brush.pbm".to_string()) })
+    );
+
+    assert!(parse("-f erode -ob outfile infile").is_err());
+    assert!(parse("-f shrink,square,1 -ob outfile infile").is_err());
+    assert!(parse("-f open,disk -ob outfile infile").is_err());
+    assert!(parse("-f open,square,-1 -ob outfile infile").is_err());
+}
+
+#[test]
+fn components() {
+    let got: ProgOpts = "-L 4 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Label(Connectivity::Four));
+    assert!("-L 6 -ob outfile infile".parse::<ProgOpts>().is_err());
+
+    let got: ProgOpts = "-T otsu --blobs json infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Threshold(Threshold::Otsu));
+    assert_eq!(got.mode, OutputMode::Blobs(BlobFormat { json: true, connectivity: Connectivity::Eight }));
+    assert_eq!(got.outfile, "");
+
+    let got: ProgOpts = "--blobs text,4 infile".parse().unwrap();
+    assert_eq!(got.mode, OutputMode::Blobs(BlobFormat { json: false, connectivity: Connectivity::Four }));
+
+    assert!("--blobs xml infile".parse::<ProgOpts>().is_err());
+    assert!("--blobs json,6 infile".parse::<ProgOpts>().is_err());
+}
+
+#[test]
+fn distance_and_skeleton() {
+    let got: ProgOpts = "-F 255 -ob outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Distance(255));
+    assert!("-F 0 -ob outfile infile".parse::<ProgOpts>().is_err());
+    assert!("-F 65536 -ob outfile infile".parse::<ProgOpts>().is_err());
+
+    let got: ProgOpts = "-z -obp outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Skeleton);
+
+    let got: ProgOpts = "-T otsu --distance outfile infile".parse().unwrap();
+    assert_eq!(got.op, ManipOption::Threshold(Threshold::Otsu));
+    assert_eq!(got.mode, OutputMode::Distances);
+    assert_eq!(got.outfile, "outfile");
+}
@@ -1,7 +1,60 @@
 use std::convert::{TryFrom, TryInto};
 use std::fs::File;
 use std::io;
-use std::io::Read;
+use std::io::{Read, Write};
+
+use crate::args::{ManipOption, OutputMode};
+
+mod balance;
+mod bilateral;
+mod blur;
+mod canny;
+mod channels;
+mod colorspace;
+mod components;
+mod convolve;
+mod distance;
+mod dither;
+mod edges;
+mod effects;
+mod equalize;
+mod grade;
+mod gray;
+mod histogram;
+mod linear;
+mod morphology;
+mod quantize;
+mod rank;
+mod resize;
+mod skeleton;
+mod stretch;
+mod stylize;
+mod threshold;
+mod tone;
+mod unsharp;
+
+use convolve::Kernel;
+pub use balance::WhiteBalance;
+pub use bilateral::Bilateral;
+pub use canny::Canny;
+pub use channels::{Channel, ChannelMixer, ChannelSource};
+pub use colorspace::{parse_component, ColorSpace};
+pub use components::{BlobReport, Connectivity};
+pub use dither::{Diffusion, Dither, DitherMethod};
+pub use edges::{EdgeDetector, GradientOutput, Operator};
+pub use effects::Duotone;
+pub use equalize::{Clahe, Equalize};
+pub use grade::ColorGrade;
+pub use gray::GrayWeights;
+pub use histogram::Histogram;
+pub use morphology::{MorphOp, Shape, StructuringElement};
+pub use quantize::{PaletteMethod, PaletteSource, Quantize};
+pub use rank::{RankFilter, Window};
+pub use stretch::Stretch;
+pub use stylize::{Emboss, Paint};
+pub use threshold::Threshold;
+pub use tone::ToneCurve;
+pub use unsharp::Unsharp;
 
 #[cfg(test)]
 mod tests;
@@ -12,8 +65,30 @@ mod tests;
 /// according to the PPM/PGM specification.
 type PxVal = u16;
 
-#[cfg_attr(test, derive(Debug, Clone, PartialEq))]
-struct ColorImage {
+/// Converts a plane of pixels into floating point values for intermediate computation.
+fn plane_to_f64(pixels: &[PxVal]) -> Vec<f64> {
+    pixels.iter().map(|&px| px as f64).collect()
+}
+
+/// Rounds a plane of floating point values back into pixels, clamping them to `0..=maxval`.
+fn plane_from_f64(plane: &[f64], maxval: usize) -> Vec<PxVal> {
+    plane.iter()
+        .map(|v| v.round().max(0.0).min(maxval as f64) as PxVal)
+        .collect()
+}
+
+/// Replaces every value `v` of a plane with `lut[v]`.
+///
+/// Values past the end of the table (which holds one entry for each value up to some `maxval`)
+/// take its last entry.
+fn apply_lut(pixels: &[PxVal], lut: &[PxVal]) -> Vec<PxVal> {
+    let last = lut.len().saturating_sub(1);
+    pixels.iter().map(|&v| lut[(v as usize).min(last)]).collect()
+}
+
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct ColorImage {
     width: usize,
     height: usize,
     maxval: usize,
@@ -22,8 +97,9 @@ struct ColorImage {
     bpixels: Vec<PxVal>,
 }
 
-#[cfg_attr(test, derive(Debug, Clone, PartialEq))]
-struct GrayImage {
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct GrayImage {
     width: usize,
     height: usize,
     maxval: usize,
@@ -37,6 +113,7 @@ enum ImageType {
 }
 
 enum ColorType {
+    Bitmap,
     Grayscale,
     Color
 }
@@ -48,6 +125,7 @@ enum RasterType {
 
 struct ImageHeader<'a> {
     is_color: bool,
+    is_bitmap: bool,
     is_ascii_raster: bool,
     width: usize,
     height: usize,
@@ -57,7 +135,7 @@ struct ImageHeader<'a> {
 
 impl<'a> ImageHeader<'a> {
 
-    /// Attempts to parse a PGM/PPM image's metadata from its full contents.
+    /// Attempts to parse a PBM/PGM/PPM image's metadata from its full contents.
     ///
     /// This function implements a state machine to read each byte at a time, parsing values as
     /// they apear and failing needed
@@ -74,9 +152,12 @@ impl<'a> ImageHeader<'a> {
     /// 7. A single whitespace
     /// 8. (raster: the actual image pixel contents)
     ///
+    /// PBM files have no maxval, so their raster starts after the height, and their maxval is
+    /// taken to be 1.
+    ///
     /// Any line (something followed by '\n' or '\r') that begins with a '#' is a comment and
     /// gets ignored until the next newline.
-    fn read(filedata: &'a [u8]) -> io::Result<ImageHeader> {
+    fn read(filedata: &'a [u8]) -> io::Result<ImageHeader<'a>> {
 
         // first determine magic sequence
         let (color_kind, raster_kind) = ImageHeader::get_kind(filedata)?;
@@ -93,7 +174,9 @@ impl<'a> ImageHeader<'a> {
         let mut scanner = filedata.iter().enumerate().skip(2);
 
         // stores values extracted from the image header:
-        // [width, height, maxval, raster_start]
+        // [width, height, maxval, raster_start] (or [width, height, raster_start] for a PBM)
+        let is_bitmap = matches!(color_kind, ColorType::Bitmap);
+        let header_len = if is_bitmap { 2 } else { 3 };
         let mut params = Vec::<usize>::with_capacity(4);
 
         // index into data where we start interpreting a value as a string
@@ -102,7 +185,7 @@ impl<'a> ImageHeader<'a> {
         // the FSM parser's state
         let mut state = State::Newline;
 
-        while params.len() < 4 {
+        while params.len() < header_len + 1 {
             match scanner.next() {
                 Some((i, &ch)) => {
                     match state {
@@ -148,7 +231,7 @@ impl<'a> ImageHeader<'a> {
 
                                                 // if we've found width, length, maxval
                                                 // then mark where the raster starts
-                                                if params.len() == 3 {
+                                                if params.len() == header_len {
                                                     params.push(i + 1);
                                                 }
                                             }
@@ -190,12 +273,17 @@ impl<'a> ImageHeader<'a> {
             }
         }
 
+        if is_bitmap {
+            params.insert(2, 1);
+        }
+
         Ok(ImageHeader {
             is_color:
                 match color_kind {
                     ColorType::Color => true,
-                    ColorType::Grayscale => false
+                    ColorType::Grayscale | ColorType::Bitmap => false
                 },
+            is_bitmap,
             is_ascii_raster:
                 match raster_kind {
                     RasterType::Ascii => true,
@@ -214,6 +302,8 @@ impl<'a> ImageHeader<'a> {
     /// or an error if the first two bytes of the file don't match any of the expected patterns.
     fn get_kind(filedata: &[u8]) -> io::Result<(ColorType, RasterType)> {
         match &filedata[0..2] {
+            b"P1" => Ok((ColorType::Bitmap,    RasterType::Ascii)),
+            b"P4" => Ok((ColorType::Bitmap,    RasterType::Raw)),
             b"P2" => Ok((ColorType::Grayscale, RasterType::Ascii)),
             b"P3" => Ok((ColorType::Color,     RasterType::Ascii)),
             b"P5" => Ok((ColorType::Grayscale, RasterType::Raw)),
@@ -221,13 +311,13 @@ impl<'a> ImageHeader<'a> {
             [one, two] => {
                 Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
-                    format!("Not a PPM/PGM file (non-magic sequence: {:?})", [one, two])
+                    format!("Not a PPM/PGM/PBM file (non-magic sequence: {:?})", [one, two])
                 ))
             },
             _ => {
                 Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
-                    format!("Not a PPM/PGM file (image too small: {} bytes)", filedata.len())
+                    format!("Not a PPM/PGM/PBM file (image too small: {} bytes)", filedata.len())
                 ))
             }
         }
@@ -257,6 +347,324 @@ impl Image {
             Ok(Image(ImageType::Grayscale(img)))
         }
     }
+
+    /// Writes the image to `basename` plus the extension matching its type (`.ppm`, `.pgm` or
+    /// `.pbm`), or for [`OutputMode::Distances`] its distance transform as a `.pfm`.
+    ///
+    /// Returns the full path of the file which was written.
+    pub fn save(&self, basename: &str, mode: &OutputMode) -> io::Result<String> {
+        let (ext, data) = match mode {
+            OutputMode::Ascii | OutputMode::Binary => self.encode(mode),
+            OutputMode::AsciiBitmap | OutputMode::BinaryBitmap => ("pbm", self.encode_bitmap(mode)?),
+            OutputMode::Distances => ("pfm", self.encode_distances()),
+            OutputMode::Stats(_) | OutputMode::Blobs(_) => return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Statistics are printed, not saved"
+            )),
+        };
+
+        let path = format!("{}.{}", basename, ext);
+        File::create(&path)?.write_all(&data)?;
+        Ok(path)
+    }
+
+    /// Encodes the image as a PPM or PGM file, returning its extension and contents.
+    fn encode(&self, mode: &OutputMode) -> (&'static str, Vec<u8>) {
+        let ascii = matches!(mode, OutputMode::Ascii);
+        let (magic, ext, width, height, maxval, planes) = match &self.0 {
+            ImageType::Color(img) => (
+                if ascii { "P3" } else { "P6" },
+                "ppm",
+                img.width,
+                img.height,
+                img.maxval,
+                vec![&img.rpixels, &img.gpixels, &img.bpixels],
+            ),
+            ImageType::Grayscale(img) => (
+                if ascii { "P2" } else { "P5" },
+                "pgm",
+                img.width,
+                img.height,
+                img.maxval,
+                vec![&img.pixels],
+            ),
+        };
+
+        let mut data = format!("{}\n{} {}\n{}\n", magic, width, height, maxval).into_bytes();
+        if ascii {
+            // one row of the image per line
+            for y in 0..height {
+                let row: Vec<String> = (y * width..(y + 1) * width)
+                    .flat_map(|i| planes.iter().map(move |p| p[i].to_string()))
+                    .collect();
+                data.extend(row.join(" ").bytes());
+                data.push(b'\n');
+            }
+        } else {
+            for i in 0..width * height {
+                for p in &planes {
+                    if maxval < 256 {
+                        data.push(p[i] as u8);
+                    } else {
+                        data.extend(&p[i].to_be_bytes());
+                    }
+                }
+            }
+        }
+
+        (ext, data)
+    }
+
+    /// Encodes a bilevel (`maxval` of 1) grayscale image as a PBM file.
+    ///
+    /// Note that PBM files use `1` for black and `0` for white, the opposite of a PGM file, so
+    /// every value is inverted.
+    fn encode_bitmap(&self, mode: &OutputMode) -> io::Result<Vec<u8>> {
+        let img = match &self.0 {
+            ImageType::Grayscale(img) if img.maxval == 1 => img,
+            _ => return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only bilevel grayscale images (maxval 1) can be written as PBM"
+            )),
+        };
+
+        let ascii = matches!(mode, OutputMode::AsciiBitmap);
+        let mut data = format!("{}\n{} {}\n", if ascii { "P1" } else { "P4" }, img.width, img.height)
+            .into_bytes();
+        for row in img.pixels.chunks(img.width.max(1)) {
+            if ascii {
+                let row: Vec<&str> = row.iter().map(|&v| if v == 0 { "1" } else { "0" }).collect();
+                data.extend(row.join(" ").bytes());
+                data.push(b'\n');
+            } else {
+                // eight pixels per byte, most significant bit first, with each row padded out
+                for byte in row.chunks(8) {
+                    let bits = byte.iter()
+                        .enumerate()
+                        .filter(|(_, &v)| v == 0)
+                        .fold(0u8, |acc, (i, _)| acc | (0x80 >> i));
+                    data.push(bits);
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    /// Encodes the distance from every foreground pixel to the background as a PFM file.
+    fn encode_distances(&self) -> Vec<u8> {
+        let (width, height, foreground) = match &self.0 {
+            ImageType::Color(img) => (img.width, img.height, img.foreground()),
+            ImageType::Grayscale(img) => (img.width, img.height, img.foreground()),
+        };
+        distance::encode_pfm(&distance::distances(&foreground, width, height), width, height)
+    }
+
+    /// Computes the histogram and summary statistics of every channel.
+    ///
+    /// See [`histogram::ChannelStats::new`] for the meaning of `bins`.
+    pub fn histogram(&self, bins: Option<usize>) -> Histogram {
+        use histogram::ChannelStats;
+        let (width, height, maxval, channels) = match &self.0 {
+            ImageType::Color(img) => (img.width, img.height, img.maxval, vec![
+                ChannelStats::new("red", &img.rpixels, img.maxval, bins),
+                ChannelStats::new("green", &img.gpixels, img.maxval, bins),
+                ChannelStats::new("blue", &img.bpixels, img.maxval, bins),
+            ]),
+            ImageType::Grayscale(img) => (img.width, img.height, img.maxval, vec![
+                ChannelStats::new("gray", &img.pixels, img.maxval, bins),
+            ]),
+        };
+        Histogram { width, height, maxval, channels }
+    }
+
+    /// Finds and measures the connected components of the image's foreground.
+    pub fn blobs(&self, connectivity: Connectivity) -> BlobReport {
+        let (width, height, foreground) = match &self.0 {
+            ImageType::Color(img) => (img.width, img.height, img.foreground()),
+            ImageType::Grayscale(img) => (img.width, img.height, img.foreground()),
+        };
+        let blobs = components::Labels::new(&foreground, width, height, connectivity).blobs();
+        BlobReport { width, height, connectivity, blobs }
+    }
+
+    /// Applies a single manipulation `op` to the image, producing a new one.
+    pub fn manipulate(&self, op: &ManipOption) -> io::Result<Image> {
+        match &self.0 {
+            ImageType::Color(img) => manipulate(img, op),
+            ImageType::Grayscale(img) => manipulate(img, op),
+        }
+    }
+
+    /// Applies `op` to the image in linear light.
+    ///
+    /// The image's gamma-encoded samples are decoded into linear samples (see
+    /// [`linear::LINEAR_MAXVAL`]), manipulated, and then encoded back to the original maxval.
+    /// Bilevel results are kept as they are, and options whose results aren't tonal are refused
+    /// (see [`linear::supports`]).
+    fn manipulate_linear(&self, op: &ManipOption) -> io::Result<Image> {
+        if !linear::supports(op) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "This option doesn't produce light, so it can't be applied in linear light"
+            ));
+        }
+
+        let maxval = self.maxval();
+        let decoded = self.map_values(&linear::decode_lut(maxval), linear::LINEAR_MAXVAL);
+        let result = decoded.manipulate(op)?;
+        match result.maxval() {
+            linear::LINEAR_MAXVAL => Ok(result.map_values(&linear::encode_lut(maxval), maxval)),
+            1 => Ok(result),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Expected a linear or bilevel result (got maxval {})", other)
+            )),
+        }
+    }
+
+    /// Applies `op` to a single component of the image in another color space.
+    ///
+    /// The component is scaled into a grayscale image (see [`colorspace::COMPONENT_MAXVAL`]),
+    /// manipulated, and written back, while the other two components keep their full precision.
+    /// `op` must produce a grayscale image of the same size; a grayscale input is treated as
+    /// color, and the result is always color.
+    fn manipulate_component(&self, space: ColorSpace, index: usize, op: &ManipOption) -> io::Result<Image> {
+        if let ManipOption::Linear(_) = op {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Components of a color space can't be manipulated in linear light"
+            ));
+        }
+        let img = match &self.0 {
+            ImageType::Color(img) => img.clone(),
+            ImageType::Grayscale(img) => img.to_color(),
+        };
+        let mut planes = img.to_space(space);
+        let component = GrayImage {
+            width: img.width,
+            height: img.height,
+            maxval: colorspace::COMPONENT_MAXVAL,
+            pixels: component_pixels(space, index, &planes[index], colorspace::COMPONENT_MAXVAL),
+        };
+
+        let result = match Image::from(component).manipulate(op)?.0 {
+            ImageType::Grayscale(result) if result.width == img.width && result.height == img.height => result,
+            _ => return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Operations on a single component must produce a grayscale image of the same size"
+            )),
+        };
+        planes[index] = result.pixels.iter()
+            .map(|&v| space.denormalize(index, v as f64 / result.maxval as f64))
+            .collect();
+        Ok(img.with_components(space, &planes).into())
+    }
+
+    fn maxval(&self) -> usize {
+        match &self.0 {
+            ImageType::Color(img) => img.maxval,
+            ImageType::Grayscale(img) => img.maxval,
+        }
+    }
+
+    /// Replaces every sample `v` with `lut[v]`, giving the result a new `maxval`.
+    fn map_values(&self, lut: &[PxVal], maxval: usize) -> Image {
+        let remap = |p: &[PxVal]| apply_lut(p, lut);
+        match &self.0 {
+            ImageType::Color(img) => ColorImage { maxval, ..img.map_planes(remap) }.into(),
+            ImageType::Grayscale(img) => GrayImage { maxval, ..img.map_planes(remap) }.into(),
+        }
+    }
+}
+
+impl From<ColorImage> for Image {
+    fn from(img: ColorImage) -> Self {
+        Image(ImageType::Color(img))
+    }
+}
+
+impl From<GrayImage> for Image {
+    fn from(img: GrayImage) -> Self {
+        Image(ImageType::Grayscale(img))
+    }
+}
+
+/// Dispatches a [`ManipOption`] to the matching [`ImageManip`] method of `img`
+fn manipulate<T>(img: &T, op: &ManipOption) -> io::Result<Image>
+where
+    T: ImageManip + Into<Image> + Clone,
+{
+    Ok(match op {
+        ManipOption::DoNothing => img.clone().into(),
+        ManipOption::Negate => img.negate().into(),
+        ManipOption::Brighten(amount) => img.brighten(*amount).into(),
+        ManipOption::Sharpen => img.sharpen().into(),
+        ManipOption::Smooth => img.smooth().into(),
+        ManipOption::Grayscale => img.grayscale().into(),
+        ManipOption::WeightedGrayscale(weights) => img.grayscale_with(weights).into(),
+        ManipOption::Contrast => img.contrast().into(),
+        ManipOption::Convolve(path) => img.convolve(&Kernel::load(path)?).into(),
+        ManipOption::GaussianBlur { sigma, fast } => img.gaussian_blur(*sigma, *fast).into(),
+        ManipOption::UnsharpMask(params) => img.unsharp_mask(params).into(),
+        ManipOption::RankFilter(params) => img.rank_filter(params).into(),
+        ManipOption::Bilateral(params) => img.bilateral(params).into(),
+        ManipOption::Edges(detector) => img.edges(detector).into(),
+        ManipOption::Canny(params) => img.canny(params).into(),
+        ManipOption::Equalize(method) => img.equalize(method).into(),
+        ManipOption::Stretch(params) => img.stretch(params).into(),
+        ManipOption::Tone(curve) => img.tone(curve).into(),
+        ManipOption::Downscale(factor) => img.downscale(*factor).into(),
+        ManipOption::Linear(inner) => img.clone().into().manipulate_linear(inner)?,
+        ManipOption::Hue(degrees) => img.grade(&ColorGrade::Hue(*degrees)).into(),
+        ManipOption::Saturation(factor) => img.grade(&ColorGrade::Saturation(*factor)).into(),
+        ManipOption::Vibrance(amount) => img.grade(&ColorGrade::Vibrance(*amount)).into(),
+        ManipOption::WhiteBalance(method) => img.white_balance(method).into(),
+        ManipOption::Channel(channel) => img.channel(*channel).into(),
+        ManipOption::Permute(order) => img.permute(order).into(),
+        ManipOption::Mix(mixer) => img.mix(mixer).into(),
+        ManipOption::Combine(sources) => {
+            let [r, g, b] = sources;
+            ColorImage::combine(
+                channel_source(img, r)?,
+                channel_source(img, g)?,
+                channel_source(img, b)?,
+            )?.into()
+        }
+        ManipOption::Threshold(method) => img.threshold(method).into(),
+        ManipOption::Dither(params) => img.dither(params).into(),
+        ManipOption::Palette(method) => img.palette(method).into(),
+        ManipOption::Quantize(params) => {
+            let palette = match &params.palette {
+                PaletteSource::Generate(method) => img.palette(method),
+                PaletteSource::File(path) => match Image::load(path)?.0 {
+                    ImageType::Color(palette) => palette,
+                    ImageType::Grayscale(palette) => palette.to_color(),
+                },
+            };
+            if palette.rpixels.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Palette has no colors"));
+            }
+            img.quantize(&palette, params).into()
+        }
+        ManipOption::Posterize(levels) => img.posterize(*levels).into(),
+        ManipOption::Solarize(threshold) => img.solarize(*threshold).into(),
+        ManipOption::Sepia => img.mix(&effects::SEPIA).into(),
+        ManipOption::Duotone(colors) => img.duotone(colors).into(),
+        ManipOption::Pixelate(block) => img.pixelate(*block).into(),
+        ManipOption::Emboss(params) => img.emboss(params).into(),
+        ManipOption::Paint(method) => img.paint(method).into(),
+        ManipOption::Sketch(sigma) => img.sketch(*sigma).into(),
+        ManipOption::Label(connectivity) => img.label(*connectivity).into(),
+        ManipOption::Distance(maxval) => img.distance(*maxval).into(),
+        ManipOption::Skeleton => img.skeleton().into(),
+        ManipOption::Morphology { op, shape } => {
+            img.morphology(*op, &StructuringElement::new(shape)?).into()
+        }
+        ManipOption::Component { space, index } => img.component(*space, *index).into(),
+        ManipOption::InComponent { space, index, op } => {
+            img.clone().into().manipulate_component(*space, *index, op)?
+        }
+    })
 }
 
 
@@ -314,7 +722,7 @@ impl TryFrom<ImageHeader<'_>> for ColorImage {
             }
         } else {
             // sz is the size of the underlying datatype
-            let sz = if hdr.maxval < 256 { 2 } else { 1 };
+            let sz = if hdr.maxval < 256 { 1 } else { 2 };
 
             // for raw files, we can quickly make sure raster size is consistent w/ header
             let expected_len = hdr.width * hdr.height * 3 * sz;
@@ -347,6 +755,10 @@ impl TryFrom<ImageHeader<'_>> for ColorImage {
             }
         }
 
+        for plane in [&rs, &gs, &bs] {
+            check_samples(plane, hdr.maxval)?;
+        }
+
         Ok(Self {
             width: hdr.width,
             height: hdr.height,
@@ -362,6 +774,15 @@ impl TryFrom<ImageHeader<'_>> for GrayImage {
     type Error = io::Error;
     fn try_from(hdr: ImageHeader) -> Result<Self, Self::Error> {
         let size = hdr.width * hdr.height;
+        if hdr.is_bitmap {
+            return Ok(Self {
+                width: hdr.width,
+                height: hdr.height,
+                maxval: 1,
+                pixels: decode_bitmap(&hdr)?,
+            });
+        }
+
         if hdr.is_ascii_raster {
             let raster = match String::from_utf8(hdr.raster.into()) {
                 Ok(inner) => inner,
@@ -394,6 +815,7 @@ impl TryFrom<ImageHeader<'_>> for GrayImage {
                     )
                 ));
             }
+            check_samples(&pixels, hdr.maxval)?;
 
             Ok(Self {
                 width: hdr.width,
@@ -403,7 +825,7 @@ impl TryFrom<ImageHeader<'_>> for GrayImage {
             })
         } else {
             // sz is the size of the underlying datatype
-            let sz = if hdr.maxval < 256 { 2 } else { 1 };
+            let sz = if hdr.maxval < 256 { 1 } else { 2 };
 
             // for raw files, we can quickly make sure raster size is consistent w/ header
             let expected_len = hdr.width * hdr.height * sz;
@@ -418,7 +840,7 @@ impl TryFrom<ImageHeader<'_>> for GrayImage {
                 ));
             }
 
-            let pixels = if hdr.maxval < 256 {
+            let pixels: Vec<PxVal> = if hdr.maxval < 256 {
                 // underlying raw pixel value is a u8
                 hdr.raster.iter()
                     .map(|px| *px as PxVal)
@@ -429,6 +851,7 @@ impl TryFrom<ImageHeader<'_>> for GrayImage {
                     .map(|px|((px[0] as PxVal) << 8) | (px[1] as PxVal))
                     .collect()
             };
+            check_samples(&pixels, hdr.maxval)?;
 
             Ok(Self {
                 width: hdr.width,
@@ -441,57 +864,724 @@ impl TryFrom<ImageHeader<'_>> for GrayImage {
 }
 
 
+/// Makes sure that no value in a raster is larger than the maxval given in its header.
+///
+/// Every manipulation relies on this, e.g. to index lookup tables of `maxval + 1` entries.
+fn check_samples(pixels: &[PxVal], maxval: usize) -> io::Result<()> {
+    match pixels.iter().find(|&&v| v as usize > maxval) {
+        Some(v) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Raster contains value '{}' larger than maxval '{}'", v, maxval)
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Decodes the raster of a PBM file into bilevel pixels.
+///
+/// PBM files use `1` for black, so every bit is inverted (as when writing them) to get a
+/// grayscale value with a maxval of 1. Plain rasters may leave out the whitespace between
+/// values, and raw rasters pad every row out to a whole byte.
+fn decode_bitmap(hdr: &ImageHeader) -> io::Result<Vec<PxVal>> {
+    let size = hdr.width * hdr.height;
+    let pixels: Vec<PxVal> = if hdr.is_ascii_raster {
+        let mut pixels = Vec::with_capacity(size);
+        for &ch in hdr.raster.iter().filter(|ch| !ch.is_ascii_whitespace()) {
+            match ch {
+                b'0' => pixels.push(1),
+                b'1' => pixels.push(0),
+                _ => return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Plain bitmap raster contains invalid value '{}'.", ch as char)
+                )),
+            }
+        }
+        pixels
+    } else {
+        let row_len = hdr.width.div_ceil(8);
+        if hdr.raster.len() != row_len * hdr.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Inconsistent raster size '{}' (expected '{}')",
+                    hdr.raster.len(),
+                    row_len * hdr.height
+                )
+            ));
+        }
+        hdr.raster.chunks(row_len.max(1))
+            .take(hdr.height)
+            .flat_map(|row| (0..hdr.width).map(move |x| if row[x / 8] & (0x80 >> (x % 8)) != 0 { 0 } else { 1 }))
+            .collect()
+    };
+
+    if pixels.len() != size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Inconsistent plain raster length '{}' (expected '{}')", pixels.len(), size)
+        ));
+    }
+    Ok(pixels)
+}
+
 ////////////////////////////////
 // Image Manipulation Methods //
 ////////////////////////////////
 
 pub trait ImageManip {
-    fn brighten(&self, amount: i32) -> ColorImage;
+    fn brighten(&self, amount: i32) -> Self;
     fn contrast(&self) -> GrayImage;
     fn grayscale(&self) -> GrayImage;
-    fn negate(&self) -> ColorImage;
-    fn sharpen(&self) -> ColorImage;
-    fn smooth(&self) -> ColorImage;
+    fn grayscale_with(&self, weights: &GrayWeights) -> GrayImage;
+    fn negate(&self) -> Self;
+    fn sharpen(&self) -> Self;
+    fn smooth(&self) -> Self;
+    fn convolve(&self, kernel: &Kernel) -> Self;
+    fn gaussian_blur(&self, sigma: f64, fast: bool) -> Self;
+    fn unsharp_mask(&self, params: &Unsharp) -> Self;
+    fn rank_filter(&self, params: &RankFilter) -> Self;
+    fn bilateral(&self, params: &Bilateral) -> Self;
+    fn edges(&self, detector: &EdgeDetector) -> GrayImage;
+    fn canny(&self, params: &Canny) -> GrayImage;
+    fn equalize(&self, method: &Equalize) -> Self;
+    fn stretch(&self, params: &Stretch) -> Self;
+    fn tone(&self, curve: &ToneCurve) -> Self;
+    fn downscale(&self, factor: usize) -> Self;
+    fn component(&self, space: ColorSpace, index: usize) -> GrayImage;
+    fn grade(&self, grade: &ColorGrade) -> Self;
+    fn white_balance(&self, method: &WhiteBalance) -> Self;
+    fn channel(&self, channel: Channel) -> GrayImage;
+    fn permute(&self, order: &[Channel; 3]) -> Self;
+    fn mix(&self, mixer: &ChannelMixer) -> ColorImage;
+    fn threshold(&self, method: &Threshold) -> GrayImage;
+    fn dither(&self, params: &Dither) -> Self;
+    fn palette(&self, method: &PaletteMethod) -> ColorImage;
+    fn quantize(&self, palette: &ColorImage, params: &Quantize) -> ColorImage;
+    fn posterize(&self, levels: usize) -> Self;
+    fn solarize(&self, threshold: f64) -> Self;
+    fn duotone(&self, colors: &Duotone) -> ColorImage;
+    fn pixelate(&self, block: usize) -> Self;
+    fn emboss(&self, params: &Emboss) -> GrayImage;
+    fn paint(&self, method: &Paint) -> Self;
+    fn sketch(&self, sigma: f64) -> GrayImage;
+    fn morphology(&self, op: MorphOp, element: &StructuringElement) -> Self;
+    fn label(&self, connectivity: Connectivity) -> ColorImage;
+    fn distance(&self, maxval: usize) -> GrayImage;
+    fn skeleton(&self) -> GrayImage;
+}
+
+/// Resolves a [`ChannelSource`] into a grayscale image, loading it if need be
+fn channel_source<T: ImageManip>(img: &T, source: &ChannelSource) -> io::Result<GrayImage> {
+    match source {
+        ChannelSource::Channel(channel) => Ok(img.channel(*channel)),
+        ChannelSource::File(path) => match Image::load(path)?.0 {
+            ImageType::Grayscale(gray) => Ok(gray),
+            ImageType::Color(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Expected a grayscale image for a channel (got color image '{}')", path)
+            )),
+        },
+    }
+}
+
+/// Adds `amount` to every value of a plane, clamping the results to `0..=maxval`
+fn brighten(pixels: &[PxVal], amount: i32, maxval: usize) -> Vec<PxVal> {
+    pixels.iter()
+        .map(|&v| (v as i64 + amount as i64).max(0).min(maxval as i64) as PxVal)
+        .collect()
+}
+
+/// Subtracts every value of a plane from `maxval`
+fn negate(pixels: &[PxVal], maxval: usize) -> Vec<PxVal> {
+    pixels.iter()
+        .map(|&v| (maxval as PxVal).saturating_sub(v))
+        .collect()
+}
+
+/// Scales one component's plane (in its natural units) into pixels from 0 to `maxval`
+fn component_pixels(space: ColorSpace, index: usize, plane: &[f64], maxval: usize) -> Vec<PxVal> {
+    let scaled: Vec<f64> = plane.iter()
+        .map(|&v| space.normalize(index, v) * maxval as f64)
+        .collect();
+    plane_from_f64(&scaled, maxval)
+}
+
+impl ColorImage {
+    /// Builds a new image of the same size by applying `f` to each color plane independently
+    fn map_planes<F>(&self, f: F) -> ColorImage
+    where
+        F: Fn(&[PxVal]) -> Vec<PxVal>
+    {
+        ColorImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            rpixels: f(&self.rpixels),
+            gpixels: f(&self.gpixels),
+            bpixels: f(&self.bpixels),
+        }
+    }
+
+    /// Flags the pixels of the foreground, which connected components are made of.
+    ///
+    /// As in a PBM file, the foreground of a bilevel image is black; otherwise it's every pixel
+    /// which isn't black.
+    fn foreground(&self) -> Vec<bool> {
+        let bilevel = self.maxval == 1;
+        self.rpixels.iter()
+            .zip(&self.gpixels)
+            .zip(&self.bpixels)
+            .map(|((&r, &g), &b)| (r != 0 || g != 0 || b != 0) != bilevel)
+            .collect()
+    }
+
+    /// Computes the (unrounded) luminance of every pixel, weighted as for grayscale conversion
+    fn luminance(&self) -> Vec<f64> {
+        GrayWeights::README.luminance(&self.rpixels, &self.gpixels, &self.bpixels)
+    }
+
+    /// Builds a new image by remapping the luminance of each pixel with `f`.
+    ///
+    /// The luminance is rounded to a plane of pixel values before being passed to `f`, and every
+    /// channel of a pixel is then scaled by the same factor to reach its new luminance, which
+    /// leaves hue and saturation alone.
+    fn map_luminance<F>(&self, f: F) -> ColorImage
+    where
+        F: Fn(&[PxVal]) -> Vec<PxVal>
+    {
+        let luma = self.luminance();
+        let new_luma = f(&plane_from_f64(&luma, self.maxval));
+        let scale = |p: &[PxVal]| -> Vec<PxVal> {
+            let scaled: Vec<f64> = (0..p.len())
+                .map(|i| {
+                    if luma[i] > 0.0 {
+                        p[i] as f64 * new_luma[i] as f64 / luma[i]
+                    } else {
+                        new_luma[i] as f64
+                    }
+                })
+                .collect();
+            plane_from_f64(&scaled, self.maxval)
+        };
+        self.map_planes(scale)
+    }
+
+    /// Builds a color image from three grayscale images, one for each channel.
+    ///
+    /// All three must have the same dimensions and maxval.
+    fn combine(r: GrayImage, g: GrayImage, b: GrayImage) -> io::Result<ColorImage> {
+        for other in [&g, &b] {
+            if (other.width, other.height, other.maxval) != (r.width, r.height, r.maxval) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Channels must match ({}x{}, maxval {} vs {}x{}, maxval {})",
+                        r.width, r.height, r.maxval, other.width, other.height, other.maxval
+                    )
+                ));
+            }
+        }
+        Ok(ColorImage {
+            width: r.width,
+            height: r.height,
+            maxval: r.maxval,
+            rpixels: r.pixels,
+            gpixels: g.pixels,
+            bpixels: b.pixels,
+        })
+    }
+
+    /// Lists the color of every pixel, in row-major order
+    fn colors(&self) -> Vec<[PxVal; 3]> {
+        (0..self.rpixels.len())
+            .map(|i| [self.rpixels[i], self.gpixels[i], self.bpixels[i]])
+            .collect()
+    }
+
+    fn plane(&self, channel: Channel) -> &[PxVal] {
+        match channel {
+            Channel::Red => &self.rpixels,
+            Channel::Green => &self.gpixels,
+            Channel::Blue => &self.bpixels,
+        }
+    }
+
+    /// Converts every pixel into `space`, returning one plane per component
+    fn to_space(&self, space: ColorSpace) -> [Vec<f64>; 3] {
+        let m = self.maxval as f64;
+        let mut planes = [Vec::new(), Vec::new(), Vec::new()];
+        for i in 0..self.rpixels.len() {
+            let rgb = [self.rpixels[i] as f64 / m, self.gpixels[i] as f64 / m, self.bpixels[i] as f64 / m];
+            for (plane, c) in planes.iter_mut().zip(space.convert(rgb).iter()) {
+                plane.push(*c);
+            }
+        }
+        planes
+    }
+
+    /// Builds an image of the same size and maxval from planes of components in `space`
+    ///
+    /// This is the inverse of [`ColorImage::to_space`].
+    fn with_components(&self, space: ColorSpace, planes: &[Vec<f64>; 3]) -> ColorImage {
+        let m = self.maxval as f64;
+        let mut rgb = [Vec::new(), Vec::new(), Vec::new()];
+        for ((&c0, &c1), &c2) in planes[0].iter().zip(&planes[1]).zip(&planes[2]) {
+            let color = space.to_rgb([c0, c1, c2]);
+            for (plane, c) in rgb.iter_mut().zip(color.iter()) {
+                plane.push(c * m);
+            }
+        }
+        ColorImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            rpixels: plane_from_f64(&rgb[0], self.maxval),
+            gpixels: plane_from_f64(&rgb[1], self.maxval),
+            bpixels: plane_from_f64(&rgb[2], self.maxval),
+        }
+    }
+}
+
+impl GrayImage {
+    /// Builds a new image of the same size by applying `f` to the pixel plane
+    fn map_planes<F>(&self, f: F) -> GrayImage
+    where
+        F: Fn(&[PxVal]) -> Vec<PxVal>
+    {
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: f(&self.pixels),
+        }
+    }
+
+    /// Flags the pixels of the foreground, which connected components are made of.
+    ///
+    /// As in a PBM file, the foreground of a bilevel image is black; otherwise it's every pixel
+    /// which isn't black.
+    fn foreground(&self) -> Vec<bool> {
+        let bilevel = self.maxval == 1;
+        self.pixels.iter().map(|&v| (v != 0) != bilevel).collect()
+    }
+
+    /// Converts to a color image whose three channels all equal this image's pixels
+    fn to_color(&self) -> ColorImage {
+        ColorImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            rpixels: self.pixels.clone(),
+            gpixels: self.pixels.clone(),
+            bpixels: self.pixels.clone(),
+        }
+    }
 }
 
 impl ImageManip for ColorImage {
-    fn brighten(&self, _amount: i32) -> ColorImage {
-        unimplemented!()
+    fn brighten(&self, amount: i32) -> ColorImage {
+        self.map_planes(|p| brighten(p, amount, self.maxval))
     }
     fn contrast(&self) -> GrayImage {
-        unimplemented!()
+        self.grayscale().contrast()
     }
     fn grayscale(&self) -> GrayImage {
-        unimplemented!()
+        self.grayscale_with(&GrayWeights::README)
+    }
+    fn grayscale_with(&self, weights: &GrayWeights) -> GrayImage {
+        let luma = weights.luminance(&self.rpixels, &self.gpixels, &self.bpixels);
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: plane_from_f64(&luma, self.maxval),
+        }
     }
     fn negate(&self) -> ColorImage {
-        unimplemented!()
+        self.map_planes(|p| negate(p, self.maxval))
     }
     fn sharpen(&self) -> ColorImage {
-        unimplemented!()
+        self.convolve(&Kernel::sharpen())
     }
     fn smooth(&self) -> ColorImage {
-        unimplemented!()
+        self.convolve(&Kernel::smooth())
+    }
+    fn convolve(&self, kernel: &Kernel) -> ColorImage {
+        self.map_planes(|p| kernel.apply(p, self.width, self.height, self.maxval))
+    }
+    fn gaussian_blur(&self, sigma: f64, fast: bool) -> ColorImage {
+        self.map_planes(|p| blur::gaussian_blur(p, self.width, self.height, self.maxval, sigma, fast))
+    }
+    fn unsharp_mask(&self, params: &Unsharp) -> ColorImage {
+        if !params.luminance {
+            return self.map_planes(|p| params.apply(p, self.width, self.height, self.maxval));
+        }
+
+        // add the same luminance detail to every channel, leaving chrominance untouched
+        let detail = params.detail(&self.luminance(), self.width, self.height);
+        self.map_planes(|p| {
+            let sharpened: Vec<f64> = p.iter()
+                .zip(detail.iter())
+                .map(|(&v, d)| v as f64 + d)
+                .collect();
+            plane_from_f64(&sharpened, self.maxval)
+        })
+    }
+    fn rank_filter(&self, params: &RankFilter) -> ColorImage {
+        self.map_planes(|p| params.apply(p, self.width, self.height, self.maxval))
+    }
+    fn bilateral(&self, params: &Bilateral) -> ColorImage {
+        let planes = [&self.rpixels[..], &self.gpixels[..], &self.bpixels[..]];
+        let mut out = params.apply(&planes, self.width, self.height, self.maxval).into_iter();
+        ColorImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            rpixels: out.next().unwrap(),
+            gpixels: out.next().unwrap(),
+            bpixels: out.next().unwrap(),
+        }
+    }
+    fn edges(&self, detector: &EdgeDetector) -> GrayImage {
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: detector.apply(&self.luminance(), self.width, self.height, self.maxval),
+        }
+    }
+    fn equalize(&self, method: &Equalize) -> ColorImage {
+        self.map_luminance(|y| method.apply(y, self.width, self.height, self.maxval))
+    }
+    fn stretch(&self, params: &Stretch) -> ColorImage {
+        if !params.luminance {
+            return self.map_planes(|p| params.apply(p, self.maxval));
+        }
+
+        let bounds = params.bounds(&plane_from_f64(&self.luminance(), self.maxval), self.maxval);
+        self.map_planes(|p| Stretch::stretch(p, self.maxval, bounds))
+    }
+    fn tone(&self, curve: &ToneCurve) -> ColorImage {
+        self.map_planes(|p| curve.apply(p, self.maxval))
+    }
+    fn downscale(&self, factor: usize) -> ColorImage {
+        let (rpixels, width, height) = resize::downscale(&self.rpixels, self.width, self.height, factor);
+        let (gpixels, _, _) = resize::downscale(&self.gpixels, self.width, self.height, factor);
+        let (bpixels, _, _) = resize::downscale(&self.bpixels, self.width, self.height, factor);
+        ColorImage { width, height, maxval: self.maxval, rpixels, gpixels, bpixels }
+    }
+    fn canny(&self, params: &Canny) -> GrayImage {
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: 1,
+            pixels: params.apply(&self.luminance(), self.width, self.height),
+        }
+    }
+    fn component(&self, space: ColorSpace, index: usize) -> GrayImage {
+        let plane = &self.to_space(space)[index];
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: component_pixels(space, index, plane, self.maxval),
+        }
+    }
+    fn grade(&self, grade: &ColorGrade) -> ColorImage {
+        let [mut h, mut s, mut l] = self.to_space(ColorSpace::Hsl);
+        for i in 0..h.len() {
+            let [gh, gs, gl] = grade.apply([h[i], s[i], l[i]]);
+            h[i] = gh;
+            s[i] = gs;
+            l[i] = gl;
+        }
+        self.with_components(ColorSpace::Hsl, &[h, s, l])
+    }
+    fn white_balance(&self, method: &WhiteBalance) -> ColorImage {
+        let [r, g, b] = method.gains([&self.rpixels, &self.gpixels, &self.bpixels], self.maxval);
+        ColorImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            rpixels: WhiteBalance::apply_gain(&self.rpixels, r, self.maxval),
+            gpixels: WhiteBalance::apply_gain(&self.gpixels, g, self.maxval),
+            bpixels: WhiteBalance::apply_gain(&self.bpixels, b, self.maxval),
+        }
+    }
+    fn channel(&self, channel: Channel) -> GrayImage {
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: self.plane(channel).to_vec(),
+        }
+    }
+    fn permute(&self, order: &[Channel; 3]) -> ColorImage {
+        ColorImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            rpixels: self.plane(order[0]).to_vec(),
+            gpixels: self.plane(order[1]).to_vec(),
+            bpixels: self.plane(order[2]).to_vec(),
+        }
+    }
+    fn mix(&self, mixer: &ChannelMixer) -> ColorImage {
+        let [rpixels, gpixels, bpixels] = mixer.apply([&self.rpixels, &self.gpixels, &self.bpixels], self.maxval);
+        ColorImage { width: self.width, height: self.height, maxval: self.maxval, rpixels, gpixels, bpixels }
+    }
+    fn threshold(&self, method: &Threshold) -> GrayImage {
+        self.grayscale().threshold(method)
+    }
+    fn dither(&self, params: &Dither) -> ColorImage {
+        ColorImage {
+            maxval: params.maxval,
+            ..self.map_planes(|p| params.apply(p, self.width, self.height, self.maxval))
+        }
+    }
+    fn palette(&self, method: &PaletteMethod) -> ColorImage {
+        let palette = method.palette(&self.colors());
+        let channel = |c: usize| palette.iter().map(|color| color[c]).collect::<Vec<f64>>();
+        ColorImage {
+            width: palette.len(),
+            height: 1,
+            maxval: self.maxval,
+            rpixels: plane_from_f64(&channel(0), self.maxval),
+            gpixels: plane_from_f64(&channel(1), self.maxval),
+            bpixels: plane_from_f64(&channel(2), self.maxval),
+        }
+    }
+    fn quantize(&self, palette: &ColorImage, params: &Quantize) -> ColorImage {
+        // bring the palette to this image's maxval
+        let scale = self.maxval as f64 / palette.maxval as f64;
+        let colors: Vec<quantize::Rgb> = palette.colors()
+            .into_iter()
+            .map(|color| color.map(|v| (v as f64 * scale).round()))
+            .collect();
+        let planes = [&self.rpixels[..], &self.gpixels[..], &self.bpixels[..]];
+        let [rpixels, gpixels, bpixels] = params.remap(planes, self.width, self.height, &colors);
+        ColorImage { width: self.width, height: self.height, maxval: self.maxval, rpixels, gpixels, bpixels }
+    }
+    fn posterize(&self, levels: usize) -> ColorImage {
+        let lut = effects::posterize_lut(levels, self.maxval);
+        self.map_planes(|p| apply_lut(p, &lut))
+    }
+    fn solarize(&self, threshold: f64) -> ColorImage {
+        let lut = effects::solarize_lut(threshold, self.maxval);
+        self.map_planes(|p| apply_lut(p, &lut))
+    }
+    fn duotone(&self, colors: &Duotone) -> ColorImage {
+        let [rpixels, gpixels, bpixels] = colors.apply(&self.luminance(), self.maxval);
+        ColorImage { width: self.width, height: self.height, maxval: self.maxval, rpixels, gpixels, bpixels }
+    }
+    fn pixelate(&self, block: usize) -> ColorImage {
+        self.map_planes(|p| effects::pixelate(p, self.width, self.height, block))
+    }
+    fn emboss(&self, params: &Emboss) -> GrayImage {
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: params.apply(&self.luminance(), self.width, self.height, self.maxval),
+        }
+    }
+    fn paint(&self, method: &Paint) -> ColorImage {
+        let planes = [&self.rpixels[..], &self.gpixels[..], &self.bpixels[..]];
+        let luma = self.luminance();
+        let mut out = method.apply(&planes, &luma, self.width, self.height, self.maxval).into_iter();
+        ColorImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            rpixels: out.next().unwrap(),
+            gpixels: out.next().unwrap(),
+            bpixels: out.next().unwrap(),
+        }
+    }
+    fn sketch(&self, sigma: f64) -> GrayImage {
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: stylize::sketch(&self.luminance(), self.width, self.height, self.maxval, sigma),
+        }
+    }
+    fn morphology(&self, op: MorphOp, element: &StructuringElement) -> ColorImage {
+        self.map_planes(|p| op.apply(element, p, self.width, self.height, self.maxval))
+    }
+    fn label(&self, connectivity: Connectivity) -> ColorImage {
+        let labels = components::Labels::new(&self.foreground(), self.width, self.height, connectivity);
+        let [rpixels, gpixels, bpixels] = labels.colorize();
+        ColorImage { width: self.width, height: self.height, maxval: 255, rpixels, gpixels, bpixels }
+    }
+    fn distance(&self, maxval: usize) -> GrayImage {
+        let distances = distance::distances(&self.foreground(), self.width, self.height);
+        GrayImage { width: self.width, height: self.height, maxval, pixels: distance::scale(&distances, maxval) }
+    }
+    fn skeleton(&self) -> GrayImage {
+        let skeleton = skeleton::thin(&self.foreground(), self.width, self.height);
+        let pixels = skeleton.iter().map(|&s| !s as PxVal).collect();
+        GrayImage { width: self.width, height: self.height, maxval: 1, pixels }
     }
 }
 
 impl ImageManip for GrayImage {
-    fn brighten(&self, _amount: i32) -> ColorImage {
-        unimplemented!()
+    fn brighten(&self, amount: i32) -> GrayImage {
+        self.map_planes(|p| brighten(p, amount, self.maxval))
     }
     fn contrast(&self) -> GrayImage {
-        unimplemented!()
+        let min = self.pixels.iter().copied().min().unwrap_or(0) as f64;
+        let max = self.pixels.iter().copied().max().unwrap_or(0) as f64;
+        if max == min {
+            // nothing to stretch
+            return self.clone();
+        }
+        let scale = self.maxval as f64 / (max - min);
+        self.map_planes(|p| {
+            let stretched: Vec<f64> = p.iter().map(|&v| scale * (v as f64 - min)).collect();
+            plane_from_f64(&stretched, self.maxval)
+        })
     }
     fn grayscale(&self) -> GrayImage {
-        unimplemented!()
+        self.clone()
     }
-    fn negate(&self) -> ColorImage {
-        unimplemented!()
+    fn grayscale_with(&self, _weights: &GrayWeights) -> GrayImage {
+        self.clone()
     }
-    fn sharpen(&self) -> ColorImage {
-        unimplemented!()
+    fn negate(&self) -> GrayImage {
+        self.map_planes(|p| negate(p, self.maxval))
     }
-    fn smooth(&self) -> ColorImage {
-        unimplemented!()
+    fn sharpen(&self) -> GrayImage {
+        self.convolve(&Kernel::sharpen())
+    }
+    fn smooth(&self) -> GrayImage {
+        self.convolve(&Kernel::smooth())
+    }
+    fn convolve(&self, kernel: &Kernel) -> GrayImage {
+        self.map_planes(|p| kernel.apply(p, self.width, self.height, self.maxval))
+    }
+    fn gaussian_blur(&self, sigma: f64, fast: bool) -> GrayImage {
+        self.map_planes(|p| blur::gaussian_blur(p, self.width, self.height, self.maxval, sigma, fast))
+    }
+    fn unsharp_mask(&self, params: &Unsharp) -> GrayImage {
+        self.map_planes(|p| params.apply(p, self.width, self.height, self.maxval))
+    }
+    fn rank_filter(&self, params: &RankFilter) -> GrayImage {
+        self.map_planes(|p| params.apply(p, self.width, self.height, self.maxval))
+    }
+    fn bilateral(&self, params: &Bilateral) -> GrayImage {
+        self.map_planes(|p| params.apply(&[p], self.width, self.height, self.maxval).remove(0))
+    }
+    fn edges(&self, detector: &EdgeDetector) -> GrayImage {
+        let plane = plane_to_f64(&self.pixels);
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: self.maxval,
+            pixels: detector.apply(&plane, self.width, self.height, self.maxval),
+        }
+    }
+    fn equalize(&self, method: &Equalize) -> GrayImage {
+        self.map_planes(|p| method.apply(p, self.width, self.height, self.maxval))
+    }
+    fn stretch(&self, params: &Stretch) -> GrayImage {
+        self.map_planes(|p| params.apply(p, self.maxval))
+    }
+    fn tone(&self, curve: &ToneCurve) -> GrayImage {
+        self.map_planes(|p| curve.apply(p, self.maxval))
+    }
+    fn downscale(&self, factor: usize) -> GrayImage {
+        let (pixels, width, height) = resize::downscale(&self.pixels, self.width, self.height, factor);
+        GrayImage { width, height, maxval: self.maxval, pixels }
+    }
+    fn canny(&self, params: &Canny) -> GrayImage {
+        let plane = plane_to_f64(&self.pixels);
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: 1,
+            pixels: params.apply(&plane, self.width, self.height),
+        }
+    }
+    fn component(&self, space: ColorSpace, index: usize) -> GrayImage {
+        self.to_color().component(space, index)
+    }
+    fn grade(&self, _grade: &ColorGrade) -> GrayImage {
+        self.clone()
+    }
+    fn white_balance(&self, _method: &WhiteBalance) -> GrayImage {
+        self.clone()
+    }
+    fn channel(&self, _channel: Channel) -> GrayImage {
+        self.clone()
+    }
+    fn permute(&self, _order: &[Channel; 3]) -> GrayImage {
+        self.clone()
+    }
+    fn mix(&self, mixer: &ChannelMixer) -> ColorImage {
+        self.to_color().mix(mixer)
+    }
+    fn threshold(&self, method: &Threshold) -> GrayImage {
+        GrayImage {
+            width: self.width,
+            height: self.height,
+            maxval: 1,
+            pixels: method.apply(&self.pixels, self.width, self.height, self.maxval),
+        }
+    }
+    fn dither(&self, params: &Dither) -> GrayImage {
+        GrayImage {
+            maxval: params.maxval,
+            ..self.map_planes(|p| params.apply(p, self.width, self.height, self.maxval))
+        }
+    }
+    fn palette(&self, method: &PaletteMethod) -> ColorImage {
+        self.to_color().palette(method)
+    }
+    fn quantize(&self, palette: &ColorImage, params: &Quantize) -> ColorImage {
+        self.to_color().quantize(palette, params)
+    }
+    fn posterize(&self, levels: usize) -> GrayImage {
+        let lut = effects::posterize_lut(levels, self.maxval);
+        self.map_planes(|p| apply_lut(p, &lut))
+    }
+    fn solarize(&self, threshold: f64) -> GrayImage {
+        let lut = effects::solarize_lut(threshold, self.maxval);
+        self.map_planes(|p| apply_lut(p, &lut))
+    }
+    fn duotone(&self, colors: &Duotone) -> ColorImage {
+        self.to_color().duotone(colors)
+    }
+    fn pixelate(&self, block: usize) -> GrayImage {
+        self.map_planes(|p| effects::pixelate(p, self.width, self.height, block))
+    }
+    fn emboss(&self, params: &Emboss) -> GrayImage {
+        let plane = plane_to_f64(&self.pixels);
+        self.map_planes(|_| params.apply(&plane, self.width, self.height, self.maxval))
+    }
+    fn paint(&self, method: &Paint) -> GrayImage {
+        let plane = plane_to_f64(&self.pixels);
+        self.map_planes(|p| method.apply(&[p], &plane, self.width, self.height, self.maxval).remove(0))
+    }
+    fn sketch(&self, sigma: f64) -> GrayImage {
+        let plane = plane_to_f64(&self.pixels);
+        self.map_planes(|_| stylize::sketch(&plane, self.width, self.height, self.maxval, sigma))
+    }
+    fn morphology(&self, op: MorphOp, element: &StructuringElement) -> GrayImage {
+        self.map_planes(|p| op.apply(element, p, self.width, self.height, self.maxval))
+    }
+    fn label(&self, connectivity: Connectivity) -> ColorImage {
+        let labels = components::Labels::new(&self.foreground(), self.width, self.height, connectivity);
+        let [rpixels, gpixels, bpixels] = labels.colorize();
+        ColorImage { width: self.width, height: self.height, maxval: 255, rpixels, gpixels, bpixels }
+    }
+    fn distance(&self, maxval: usize) -> GrayImage {
+        let distances = distance::distances(&self.foreground(), self.width, self.height);
+        GrayImage { width: self.width, height: self.height, maxval, pixels: distance::scale(&distances, maxval) }
+    }
+    fn skeleton(&self) -> GrayImage {
+        let skeleton = skeleton::thin(&self.foreground(), self.width, self.height);
+        let pixels = skeleton.iter().map(|&s| !s as PxVal).collect();
+        GrayImage { width: self.width, height: self.height, maxval: 1, pixels }
     }
 }
@@ -0,0 +1,85 @@
+use super::rank::RankHistogram;
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// A white balance correction, applied to a color image as a gain for each channel.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum WhiteBalance {
+    /// Assume the scene averages to gray, scaling each channel's mean to the mean of all three
+    GrayWorld,
+    /// Assume the brightest value of each channel belongs to something white (max-RGB), scaling
+    /// it to `maxval`
+    WhitePatch,
+    /// Like `WhitePatch`, but use the value at this percentile of each channel (e.g. 99), so a
+    /// few specular highlights or hot pixels can't decide the result
+    Percentile(f64),
+    /// Correct for light of a known color temperature (in Kelvin), and then shift by `tint` from
+    /// green (negative) to magenta (positive), from -1 to 1
+    Temperature { kelvin: f64, tint: f64 },
+}
+
+/// The temperature which is left unchanged by [`WhiteBalance::Temperature`] (roughly daylight,
+/// and the white point of sRGB)
+const NEUTRAL_KELVIN: f64 = 6500.0;
+
+impl WhiteBalance {
+    /// Computes the gain of each of the red, green and blue `planes`.
+    ///
+    /// A channel with nothing to measure (e.g. all zeros) gets a gain of 1.
+    pub fn gains(&self, planes: [&[PxVal]; 3], maxval: usize) -> [f64; 3] {
+        let ratio = |target: f64, v: f64| if v > 0.0 { target / v } else { 1.0 };
+        match *self {
+            WhiteBalance::GrayWorld => {
+                let means = planes.map(|p| p.iter().map(|&v| v as f64).sum::<f64>() / p.len().max(1) as f64);
+                let gray = means.iter().sum::<f64>() / 3.0;
+                means.map(|m| ratio(gray, m))
+            }
+            WhiteBalance::WhitePatch => WhiteBalance::Percentile(100.0).gains(planes, maxval),
+            WhiteBalance::Percentile(p) => planes.map(|plane| {
+                let mut hist = RankHistogram::new(maxval);
+                for &v in plane {
+                    hist.add(v);
+                }
+                ratio(maxval as f64, hist.percentile(p) as f64)
+            }),
+            WhiteBalance::Temperature { kelvin, tint } => {
+                let (light, neutral) = (blackbody(kelvin), blackbody(NEUTRAL_KELVIN));
+                let gains = [0, 1, 2].map(|c| ratio(neutral[c], light[c]));
+                // keep green's gain at 1, as cameras do, so overall brightness barely changes
+                [gains[0] / gains[1], 1.0 - tint / 2.0, gains[2] / gains[1]]
+            }
+        }
+    }
+
+    /// Multiplies a plane by `gain`, clamping the results to `maxval`.
+    pub fn apply_gain(pixels: &[PxVal], gain: f64, maxval: usize) -> Vec<PxVal> {
+        pixels.iter()
+            .map(|&v| (v as f64 * gain).round().min(maxval as f64) as PxVal)
+            .collect()
+    }
+}
+
+/// Approximates the color of a black body at `kelvin` degrees, as red, green and blue from 0 to 1.
+///
+/// This is Tanner Helland's fit to Mitchell Charity's blackbody table, which is accurate to
+/// within a percent or so from 1000K to 40000K.
+fn blackbody(kelvin: f64) -> [f64; 3] {
+    let t = kelvin / 100.0;
+    let red = if t <= 66.0 { 255.0 } else { 329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2) };
+    let green = if t <= 66.0 {
+        99.470_802_586_1 * t.ln() - 161.119_568_166_1
+    } else {
+        288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+    };
+    [red, green, blue].map(|c| c.clamp(0.0, 255.0) / 255.0)
+}
@@ -0,0 +1,92 @@
+///////////////////////////////
+// unit tests for balance.rs //
+///////////////////////////////
+use super::*;
+use crate::image::{ColorImage, GrayImage, ImageManip};
+
+/// Grays with a blue cast: blue is 5/4 of red and green
+fn blue_cast() -> ColorImage {
+    ColorImage {
+        width: 4,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![40, 80, 120, 160],
+        gpixels: vec![40, 80, 120, 160],
+        bpixels: vec![50, 100, 150, 200],
+    }
+}
+
+fn assert_gains(got: [f64; 3], expected: [f64; 3]) {
+    for c in 0..3 {
+        assert!((got[c] - expected[c]).abs() < 1e-9, "{:?} != {:?}", got, expected);
+    }
+}
+
+#[test]
+fn gray_world() {
+    let img = blue_cast();
+    let gains = WhiteBalance::GrayWorld.gains([&img.rpixels, &img.gpixels, &img.bpixels], 255);
+    // the channel means are 100, 100 and 125, so the target gray is 325 / 3
+    assert_gains(gains, [325.0 / 300.0, 325.0 / 300.0, 325.0 / 375.0]);
+
+    let out = img.white_balance(&WhiteBalance::GrayWorld);
+    assert_eq!(out.rpixels, vec![43, 87, 130, 173]);
+    assert_eq!(out.rpixels, out.gpixels);
+    assert_eq!(out.rpixels, out.bpixels);
+}
+
+#[test]
+fn white_patch_and_percentile() {
+    let img = blue_cast();
+    let planes = [&img.rpixels[..], &img.gpixels[..], &img.bpixels[..]];
+    assert_gains(WhiteBalance::WhitePatch.gains(planes, 255), [255.0 / 160.0, 255.0 / 160.0, 255.0 / 200.0]);
+    assert_gains(WhiteBalance::Percentile(50.0).gains(planes, 255), [255.0 / 120.0, 255.0 / 120.0, 255.0 / 150.0]);
+
+    let out = img.white_balance(&WhiteBalance::WhitePatch);
+    assert_eq!(out.rpixels, vec![64, 128, 191, 255]);
+    // both middle values are exactly 127.5 before rounding, so allow for error in the gains
+    assert!(out.bpixels.iter().zip(&out.rpixels).all(|(&b, &r)| (b as i32 - r as i32).abs() <= 1));
+
+    // percentile gains push the brightest pixels past maxval, which clamps them
+    let out = img.white_balance(&WhiteBalance::Percentile(50.0));
+    assert_eq!(out.gpixels, vec![85, 170, 255, 255]);
+}
+
+#[test]
+fn empty_channels_are_left_alone() {
+    let planes: [&[PxVal]; 3] = [&[0, 0], &[10, 20], &[0, 0]];
+    assert_gains(WhiteBalance::GrayWorld.gains(planes, 255), [1.0, 1.0 / 3.0, 1.0]);
+    assert_gains(WhiteBalance::WhitePatch.gains(planes, 255), [1.0, 255.0 / 20.0, 1.0]);
+}
+
+#[test]
+fn color_temperature() {
+    let planes: [&[PxVal]; 3] = [&[], &[], &[]];
+    let gains = |kelvin, tint| WhiteBalance::Temperature { kelvin, tint }.gains(planes, 255);
+
+    assert_gains(gains(NEUTRAL_KELVIN, 0.0), [1.0, 1.0, 1.0]);
+    // warm (tungsten) light needs less red and more blue, and cool light the opposite
+    let warm = gains(3000.0, 0.0);
+    assert!(warm[0] < 1.0 && warm[2] > 1.5);
+    let cool = gains(10000.0, 0.0);
+    assert!(cool[0] > 1.0 && cool[2] < 1.0);
+
+    assert_gains(gains(NEUTRAL_KELVIN, 0.5), [1.0, 0.75, 1.0]);
+    assert_gains(gains(NEUTRAL_KELVIN, -1.0), [1.0, 1.5, 1.0]);
+}
+
+#[test]
+fn blackbody_colors() {
+    let [r, g, b] = blackbody(2000.0);
+    assert!(r == 1.0 && g < 0.6 && b < 0.2);
+    let [r, g, b] = blackbody(6600.0);
+    assert!(r == 1.0 && g > 0.99 && b == 1.0);
+    let [r, g, b] = blackbody(20000.0);
+    assert!(r < g && g < b);
+}
+
+#[test]
+fn grays_are_unchanged() {
+    let gray = GrayImage { width: 2, height: 1, maxval: 15, pixels: vec![3, 12] };
+    assert_eq!(gray.white_balance(&WhiteBalance::WhitePatch), gray);
+}
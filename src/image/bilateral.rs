@@ -0,0 +1,73 @@
+use super::convolve::EdgeMode;
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// Parameters of an edge-preserving bilateral filter.
+///
+/// Each value becomes a weighted average of its neighbors, where a neighbor's weight falls off
+/// both with its distance from the center (`spatial`, in pixels) and with how different it is
+/// from the center value (`range`, as a fraction of `maxval`). Neighbors across an edge differ
+/// greatly, so they contribute little and the edge stays sharp.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Bilateral {
+    pub spatial: f64,
+    pub range: f64,
+}
+
+impl Bilateral {
+    /// Filters several planes of the same image together.
+    ///
+    /// The range distance between two pixels is measured jointly across every plane (e.g. the
+    /// euclidean distance between two RGB colors), so all planes get the same weights and colors
+    /// don't shift. Neighbors which fall off an edge take the value of the nearest pixel on it.
+    pub fn apply(&self, planes: &[&[PxVal]], width: usize, height: usize, maxval: usize) -> Vec<Vec<PxVal>> {
+        let radius = (3.0 * self.spatial).ceil() as isize;
+        let spatial: Vec<f64> = (-radius..=radius)
+            .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| (-((dx * dx + dy * dy) as f64) / (2.0 * self.spatial * self.spatial)).exp())
+            .collect();
+        let sigma_r = self.range * maxval as f64;
+        let range_coeff = -1.0 / (2.0 * sigma_r * sigma_r);
+
+        let mut out = vec![Vec::with_capacity(width * height); planes.len()];
+        let mut sums = vec![0.0; planes.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let center = y * width + x;
+                let mut total = 0.0;
+                sums.iter_mut().for_each(|s| *s = 0.0);
+
+                let mut k = 0;
+                for dy in -radius..=radius {
+                    let sy = EdgeMode::Replicate.resolve(y as isize + dy, height).unwrap();
+                    for dx in -radius..=radius {
+                        let sx = EdgeMode::Replicate.resolve(x as isize + dx, width).unwrap();
+                        let i = sy * width + sx;
+
+                        let dist2: f64 = planes.iter()
+                            .map(|p| {
+                                let d = p[i] as f64 - p[center] as f64;
+                                d * d
+                            })
+                            .sum();
+                        let weight = spatial[k] * (dist2 * range_coeff).exp();
+                        k += 1;
+
+                        total += weight;
+                        for (s, p) in sums.iter_mut().zip(planes.iter()) {
+                            *s += weight * p[i] as f64;
+                        }
+                    }
+                }
+
+                for (o, s) in out.iter_mut().zip(sums.iter()) {
+                    o.push((s / total).round().min(maxval as f64) as PxVal);
+                }
+            }
+        }
+        out
+    }
+}
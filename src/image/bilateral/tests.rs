@@ -0,0 +1,71 @@
+/////////////////////////////////
+// unit tests for bilateral.rs //
+/////////////////////////////////
+use super::*;
+use crate::image::blur::gaussian_blur;
+
+/// An 8x8 image: the left half dark, the right half light, both with slight noise
+fn noisy_edge() -> Vec<PxVal> {
+    (0..64)
+        .map(|i| {
+            let noise = [0, 3, 1, 4, 2][i % 5];
+            if i % 8 < 4 { 40 + noise } else { 200 + noise }
+        })
+        .collect()
+}
+
+#[test]
+fn constant_plane_unchanged() {
+    let pixels = vec![77; 30];
+    let f = Bilateral { spatial: 2.0, range: 0.1 };
+    assert_eq!(f.apply(&[&pixels], 6, 5, 255), vec![pixels]);
+}
+
+#[test]
+fn preserves_edges() {
+    let pixels = noisy_edge();
+    let f = Bilateral { spatial: 2.0, range: 0.1 };
+    let out = &f.apply(&[&pixels], 8, 8, 255)[0];
+
+    // values on either side of the edge stay on that side
+    for y in 0..8 {
+        assert!(out[y * 8 + 3] <= 45);
+        assert!(out[y * 8 + 4] >= 200);
+    }
+
+    // whereas a gaussian of the same size blurs them together
+    let blurred = gaussian_blur(&pixels, 8, 8, 255, 2.0, false);
+    assert!(blurred[3] > 60);
+}
+
+#[test]
+fn reduces_noise() {
+    let pixels = noisy_edge();
+    let f = Bilateral { spatial: 2.0, range: 0.1 };
+    let out = &f.apply(&[&pixels], 8, 8, 255)[0];
+
+    let spread = |p: &[PxVal]| {
+        let left: Vec<PxVal> = (0..64).filter(|i| i % 8 < 4).map(|i| p[i]).collect();
+        left.iter().max().unwrap() - left.iter().min().unwrap()
+    };
+    assert!(spread(out) < spread(&pixels));
+}
+
+#[test]
+fn color_planes_share_weights() {
+    // red has a big edge down the middle and a small step across it, and green the other way
+    // round, so each plane's small step is only kept apart by the other plane's big edge
+    let red: Vec<PxVal> = (0..16).map(|i| if i % 4 < 2 { 0 } else { 200 } + if i < 8 { 0 } else { 6 }).collect();
+    let green: Vec<PxVal> = (0..16).map(|i| if i < 8 { 0 } else { 240 } + if i % 4 < 2 { 0 } else { 6 }).collect();
+    let f = Bilateral { spatial: 1.0, range: 0.05 };
+
+    // on their own, each small step is smoothed over
+    assert_ne!(f.apply(&[&red], 4, 4, 255)[0], red);
+    assert_ne!(f.apply(&[&green], 4, 4, 255)[0], green);
+
+    // but weighted jointly, every quadrant is far from the others, so nothing is averaged across
+    let out = f.apply(&[&red, &green, &green], 4, 4, 255);
+    assert_eq!(out[0], red);
+    assert_eq!(out[1], green);
+    assert_eq!(out[2], green);
+}
@@ -0,0 +1,112 @@
+use super::convolve::{EdgeMode, Kernel};
+use super::{plane_from_f64, plane_to_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// Builds a normalized, separable gaussian kernel for the given standard deviation.
+///
+/// The kernel extends to `ceil(3 * sigma)` values on either side of its center, which captures
+/// over 99.7% of the gaussian's weight.
+pub fn gaussian_kernel(sigma: f64) -> Kernel {
+    let radius = (3.0 * sigma).ceil().max(0.0) as isize;
+    let mut taps: Vec<f64> = (-radius..=radius)
+        .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f64 = taps.iter().sum();
+    for t in taps.iter_mut() {
+        *t /= sum;
+    }
+
+    Kernel::separable(taps.clone(), taps)
+        .unwrap()
+        .with_divisor(1.0)
+        .with_edge(EdgeMode::Replicate)
+}
+
+/// Computes the widths of `n` successive box blurs which together approximate a gaussian.
+///
+/// Each width is odd, and they differ by at most 2, chosen so that the variance of the combined
+/// blur is as close as possible to `sigma^2` (see W. Wells, "Efficient synthesis of gaussian
+/// filters by cascaded uniform filters", 1986).
+pub fn box_sizes(sigma: f64, n: usize) -> Vec<usize> {
+    let n_f = n as f64;
+    let ideal = (12.0 * sigma * sigma / n_f + 1.0).sqrt();
+    let mut lower = ideal.floor() as usize;
+    if lower.is_multiple_of(2) {
+        lower = lower.saturating_sub(1);
+    }
+    let lower = lower.max(1);
+    let upper = lower + 2;
+
+    let l = lower as f64;
+    let m_ideal = (12.0 * sigma * sigma - n_f * l * l - 4.0 * n_f * l - 3.0 * n_f) / (-4.0 * l - 4.0);
+    let m = m_ideal.round().max(0.0) as usize;
+
+    (0..n).map(|i| if i < m { lower } else { upper }).collect()
+}
+
+/// Blurs a plane with an unweighted `(2*radius + 1)`-wide box, replicating edge values.
+///
+/// This runs in time independent of `radius` by sliding a running sum across each row, and then
+/// down each column.
+pub fn box_blur(plane: &[f64], width: usize, height: usize, radius: usize) -> Vec<f64> {
+    let tmp = box_pass(plane, width, height, radius, true);
+    box_pass(&tmp, width, height, radius, false)
+}
+
+/// One dimension of a running-sum box blur
+fn box_pass(plane: &[f64], width: usize, height: usize, radius: usize, horizontal: bool) -> Vec<f64> {
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let (lines, len) = if horizontal { (height, width) } else { (width, height) };
+    let at = |line: usize, i: isize| -> f64 {
+        let i = EdgeMode::Replicate.resolve(i, len).unwrap();
+        if horizontal { plane[line * width + i] } else { plane[i * width + line] }
+    };
+
+    let r = radius as isize;
+    let size = (2 * radius + 1) as f64;
+    let mut out = vec![0.0; plane.len()];
+    for line in 0..lines {
+        let mut sum: f64 = (-r..=r).map(|i| at(line, i)).sum();
+        for i in 0..len {
+            let idx = if horizontal { line * width + i } else { i * width + line };
+            out[idx] = sum / size;
+            sum += at(line, i as isize + r + 1) - at(line, i as isize - r);
+        }
+    }
+    out
+}
+
+/// Blurs a floating point plane with a gaussian of standard deviation `sigma`.
+///
+/// When `fast` is set, three successive box blurs are used instead of a true gaussian kernel,
+/// which takes the same time for any `sigma` at the cost of some accuracy.
+pub fn gaussian_plane(plane: &[f64], width: usize, height: usize, sigma: f64, fast: bool) -> Vec<f64> {
+    if sigma <= 0.0 {
+        return plane.to_vec();
+    }
+
+    if fast {
+        box_sizes(sigma, 3)
+            .into_iter()
+            .fold(plane.to_vec(), |p, size| box_blur(&p, width, height, size / 2))
+    } else {
+        gaussian_kernel(sigma).convolve(plane, width, height)
+    }
+}
+
+/// Blurs a plane of pixels with a gaussian of standard deviation `sigma`.
+pub fn gaussian_blur(
+    pixels: &[PxVal],
+    width: usize,
+    height: usize,
+    maxval: usize,
+    sigma: f64,
+    fast: bool,
+) -> Vec<PxVal> {
+    let plane = plane_to_f64(pixels);
+    plane_from_f64(&gaussian_plane(&plane, width, height, sigma, fast), maxval)
+}
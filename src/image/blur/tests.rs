@@ -0,0 +1,99 @@
+////////////////////////////
+// unit tests for blur.rs //
+////////////////////////////
+use super::*;
+
+/// A `size`x`size` plane of zeros with a single `1.0` in its center
+fn impulse(size: usize) -> Vec<f64> {
+    let mut plane = vec![0.0; size * size];
+    plane[size * size / 2] = 1.0;
+    plane
+}
+
+fn assert_symmetric(plane: &[f64], size: usize) {
+    for y in 0..size {
+        for x in 0..size {
+            let v = plane[y * size + x];
+            assert!((v - plane[y * size + (size - 1 - x)]).abs() < 1e-12);
+            assert!((v - plane[(size - 1 - y) * size + x]).abs() < 1e-12);
+            assert!((v - plane[x * size + y]).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn kernel_is_normalized() {
+    for &sigma in &[0.5f64, 1.0, 2.5, 7.0] {
+        // convolving an impulse reproduces the kernel itself
+        let radius = (3.0 * sigma).ceil() as usize;
+        let size = 2 * radius + 3;
+        let weights = gaussian_kernel(sigma).convolve(&impulse(size), size, size);
+        let sum: f64 = weights.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+
+        // and nothing lies beyond its radius
+        assert_eq!(weights[0], 0.0);
+        assert!(weights[size + 1] > 0.0);
+    }
+}
+
+#[test]
+fn exact_blur_preserves_energy() {
+    let blurred = gaussian_plane(&impulse(41), 41, 41, 3.0, false);
+    let sum: f64 = blurred.iter().sum();
+    assert!((sum - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn fast_blur_preserves_energy() {
+    let blurred = gaussian_plane(&impulse(41), 41, 41, 3.0, true);
+    let sum: f64 = blurred.iter().sum();
+    assert!((sum - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn blurs_are_symmetric() {
+    assert_symmetric(&gaussian_plane(&impulse(31), 31, 31, 2.0, false), 31);
+    assert_symmetric(&gaussian_plane(&impulse(31), 31, 31, 2.0, true), 31);
+}
+
+#[test]
+fn fast_approximates_exact() {
+    let exact = gaussian_plane(&impulse(61), 61, 61, 5.0, false);
+    let fast = gaussian_plane(&impulse(61), 61, 61, 5.0, true);
+    let peak = exact[61 * 61 / 2];
+    for (e, f) in exact.iter().zip(fast.iter()) {
+        assert!((e - f).abs() < 0.1 * peak);
+    }
+}
+
+#[test]
+fn box_sizes_match_variance() {
+    for &sigma in &[1.0, 2.0, 4.5, 20.0] {
+        let sizes = box_sizes(sigma, 3);
+        assert!(sizes.iter().all(|s| s % 2 == 1));
+        let variance: f64 = sizes.iter().map(|&s| ((s * s) as f64 - 1.0) / 12.0).sum();
+        assert!((variance.sqrt() - sigma).abs() < 0.5);
+    }
+}
+
+#[test]
+fn box_blur_of_constant_is_constant() {
+    let plane = vec![5.0; 7 * 4];
+    for v in box_blur(&plane, 7, 4, 10) {
+        assert!((v - 5.0).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn zero_sigma_is_identity() {
+    let pixels: Vec<PxVal> = (0..12).collect();
+    assert_eq!(gaussian_blur(&pixels, 4, 3, 15, 0.0, false), pixels);
+}
+
+#[test]
+fn empty_planes_blur() {
+    assert!(box_blur(&[], 0, 5, 2).is_empty());
+    assert!(box_blur(&[], 5, 0, 2).is_empty());
+    assert!(gaussian_blur(&[], 0, 0, 255, 2.0, true).is_empty());
+}
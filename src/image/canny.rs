@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use super::blur::gaussian_plane;
+use super::edges::Operator;
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// Parameters of a Canny edge detector.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Canny {
+    /// Standard deviation of the gaussian blur applied before finding gradients
+    pub sigma: f64,
+    /// The `(low, high)` hysteresis thresholds, as fractions of the strongest gradient magnitude.
+    ///
+    /// When `None`, they're chosen automatically from the histogram of gradient magnitudes.
+    pub thresholds: Option<(f64, f64)>,
+}
+
+/// Fraction of pixels assumed not to be edges when choosing the high threshold automatically
+const NON_EDGE_FRACTION: f64 = 0.7;
+
+/// Ratio of the low threshold to the high threshold, when choosing them automatically
+const LOW_HIGH_RATIO: f64 = 0.4;
+
+/// Number of bins in the histogram of gradient magnitudes
+const HISTOGRAM_BINS: usize = 256;
+
+impl Canny {
+    /// Finds the edges of a plane, returning a bilevel plane where edges are 1 and all else is 0.
+    ///
+    /// The pipeline is:
+    /// 1. blur the plane with a gaussian
+    /// 2. find its gradient with the Sobel operator
+    /// 3. thin edges by suppressing any magnitude which isn't a maximum along its gradient
+    /// 4. keep every pixel above the high threshold, and every pixel above the low threshold
+    ///    which is (8-)connected to one of those
+    pub fn apply(&self, plane: &[f64], width: usize, height: usize) -> Vec<PxVal> {
+        let smoothed = gaussian_plane(plane, width, height, self.sigma, false);
+        let (gx, gy) = Operator::Sobel.gradient(&smoothed, width, height);
+        let magnitude: Vec<f64> = gx.iter().zip(gy.iter()).map(|(x, y)| x.hypot(*y)).collect();
+
+        let largest = magnitude.iter().cloned().fold(0.0, f64::max);
+        if largest == 0.0 {
+            return vec![0; plane.len()];
+        }
+
+        let thinned = suppress_non_maxima(&magnitude, &gx, &gy, width, height);
+        let (low, high) = self.thresholds.unwrap_or_else(|| auto_thresholds(&magnitude, largest));
+        hysteresis(&thinned, width, height, low * largest, high * largest)
+    }
+}
+
+/// Chooses `(low, high)` thresholds (as fractions of `largest`) from a histogram of magnitudes.
+///
+/// The high threshold is placed so that [`NON_EDGE_FRACTION`] of all pixels fall below it.
+pub fn auto_thresholds(magnitude: &[f64], largest: f64) -> (f64, f64) {
+    let mut histogram = [0usize; HISTOGRAM_BINS];
+    for m in magnitude {
+        let bin = ((m / largest) * (HISTOGRAM_BINS - 1) as f64).round() as usize;
+        histogram[bin] += 1;
+    }
+
+    let target = (NON_EDGE_FRACTION * magnitude.len() as f64).ceil() as usize;
+    let mut seen = 0;
+    let mut bin = 0;
+    while bin < HISTOGRAM_BINS {
+        seen += histogram[bin];
+        if seen >= target {
+            break;
+        }
+        bin += 1;
+    }
+
+    let high = ((bin + 1) as f64 / HISTOGRAM_BINS as f64).min(1.0);
+    (LOW_HIGH_RATIO * high, high)
+}
+
+/// Zeroes every magnitude which is smaller than either neighbor along its gradient direction.
+///
+/// The direction is rounded to the nearest of horizontal, vertical, or either diagonal.
+fn suppress_non_maxima(magnitude: &[f64], gx: &[f64], gy: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let at = |x: isize, y: isize| -> f64 {
+        if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+            0.0
+        } else {
+            magnitude[y as usize * width + x as usize]
+        }
+    };
+
+    let mut out = vec![0.0; magnitude.len()];
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let i = y as usize * width + x as usize;
+            let m = magnitude[i];
+            if m == 0.0 {
+                continue;
+            }
+
+            // fold the angle into 0..180 degrees, then pick one of four sectors
+            let angle = gy[i].atan2(gx[i]).to_degrees().rem_euclid(180.0);
+            let (dx, dy) = if !(22.5..157.5).contains(&angle) {
+                (1, 0)
+            } else if angle < 67.5 {
+                (1, 1)
+            } else if angle < 112.5 {
+                (0, 1)
+            } else {
+                (-1, 1)
+            };
+
+            if m >= at(x + dx, y + dy) && m >= at(x - dx, y - dy) {
+                out[i] = m;
+            }
+        }
+    }
+    out
+}
+
+/// Traces edges from every value above `high` through connected values above `low`.
+fn hysteresis(magnitude: &[f64], width: usize, height: usize, low: f64, high: f64) -> Vec<PxVal> {
+    let mut edges = vec![0; magnitude.len()];
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for (i, &m) in magnitude.iter().enumerate() {
+        if m >= high && m > 0.0 {
+            edges[i] = 1;
+            queue.push_back(i);
+        }
+    }
+
+    while let Some(i) = queue.pop_front() {
+        let (x, y) = ((i % width) as isize, (i / width) as isize);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let n = ny as usize * width + nx as usize;
+                if edges[n] == 0 && magnitude[n] >= low && magnitude[n] > 0.0 {
+                    edges[n] = 1;
+                    queue.push_back(n);
+                }
+            }
+        }
+    }
+    edges
+}
@@ -0,0 +1,110 @@
+/////////////////////////////
+// unit tests for canny.rs //
+/////////////////////////////
+use super::*;
+use crate::args::OutputMode;
+use crate::image::{GrayImage, Image, ImageManip};
+
+/// A `size`x`size` plane with a bright square in the middle
+fn square(size: usize) -> Vec<f64> {
+    (0..size * size)
+        .map(|i| {
+            let (x, y) = (i % size, i / size);
+            let inside = |v: usize| v >= size / 4 && v < size - size / 4;
+            if inside(x) && inside(y) { 200.0 } else { 20.0 }
+        })
+        .collect()
+}
+
+#[test]
+fn flat_plane_has_no_edges() {
+    let canny = Canny { sigma: 1.0, thresholds: None };
+    assert_eq!(canny.apply(&vec![10.0; 64], 8, 8), vec![0; 64]);
+}
+
+#[test]
+fn edges_are_thin() {
+    let canny = Canny { sigma: 1.0, thresholds: Some((0.2, 0.5)) };
+    let edges = canny.apply(&square(24), 24, 24);
+
+    // each row through the middle of the square crosses exactly two edges, one pixel wide
+    for y in 8..16 {
+        let row = &edges[y * 24..(y + 1) * 24];
+        let crossings = row.windows(2).filter(|w| w[0] != w[1]).count();
+        assert_eq!(crossings, 4, "row {}: {:?}", y, row);
+    }
+
+    // and nothing far from the square's boundary is an edge
+    assert_eq!(edges[0], 0);
+    assert_eq!(edges[12 * 24 + 12], 0);
+}
+
+#[test]
+fn hysteresis_follows_weak_edges() {
+    // a strong pixel connected to a chain of weak pixels, plus an isolated weak pixel
+    let magnitude = vec![
+        9.0, 3.0, 3.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 3.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0,
+        3.0, 0.0, 0.0, 0.0, 0.0,
+    ];
+    let edges = hysteresis(&magnitude, 5, 4, 2.0, 5.0);
+    assert_eq!(edges, vec![
+        1, 1, 1, 0, 0,
+        0, 0, 0, 1, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+    ]);
+}
+
+#[test]
+fn automatic_thresholds() {
+    // 70% of the values are at most 0.5, so that's where the high threshold lands
+    let magnitude: Vec<f64> = (0..10).map(|i| if i < 7 { 0.5 } else { 1.0 }).collect();
+    let (low, high) = auto_thresholds(&magnitude, 1.0);
+    assert!(high > 0.5 && high < 0.51);
+    assert!((low - 0.4 * high).abs() < 1e-12);
+
+    let canny = Canny { sigma: 1.0, thresholds: None };
+    let edges = canny.apply(&square(24), 24, 24);
+    assert!(edges.contains(&1));
+}
+
+#[test]
+fn canny_image_is_bilevel() {
+    let img = GrayImage { width: 24, height: 24, maxval: 255, pixels: vec![0; 576] };
+    let img = GrayImage { pixels: square(24).iter().map(|&v| v as PxVal).collect(), ..img };
+    let edges = img.canny(&Canny { sigma: 1.0, thresholds: None });
+    assert_eq!(edges.maxval, 1);
+    assert!(edges.pixels.iter().all(|&v| v <= 1));
+}
+
+#[test]
+fn encode_pbm() {
+    let img = Image::from(GrayImage {
+        width: 10,
+        height: 2,
+        maxval: 1,
+        pixels: vec![
+            1, 0, 1, 1, 1, 1, 1, 1, 1, 0,
+            1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        ],
+    });
+
+    let plain = img.encode_bitmap(&OutputMode::AsciiBitmap).unwrap();
+    assert_eq!(
+        String::from_utf8(plain).unwrap(),
+        "P1\n10 2\n0 1 0 0 0 0 0 0 0 1\n0 0 0 0 0 0 0 0 0 0\n"
+    );
+
+    // rows are padded out to a whole number of bytes
+    let raw = img.encode_bitmap(&OutputMode::BinaryBitmap).unwrap();
+    assert_eq!(&raw[..8], b"P4\n10 2\n");
+    assert_eq!(&raw[8..], &[0b0100_0000, 0b0100_0000, 0, 0]);
+}
+
+#[test]
+fn pbm_needs_bilevel_image() {
+    let img = Image::from(GrayImage { width: 1, height: 1, maxval: 255, pixels: vec![1] });
+    assert!(img.encode_bitmap(&OutputMode::BinaryBitmap).is_err());
+}
@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use super::{plane_from_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// One of the three channels of a color image.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+}
+
+impl FromStr for Channel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "r" | "red" => Ok(Channel::Red),
+            "g" | "green" => Ok(Channel::Green),
+            "b" | "blue" => Ok(Channel::Blue),
+            other => Err(format!("Unknown channel '{}'", other)),
+        }
+    }
+}
+
+/// Where one channel of a combined image comes from.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ChannelSource {
+    /// A channel of the image being manipulated (or, if it's grayscale, the image itself)
+    Channel(Channel),
+    /// A grayscale image read from the named file
+    File(String),
+}
+
+impl FromStr for ChannelSource {
+    type Err = String;
+
+    /// Parses a channel name, or else treats `s` as the path to a PGM.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("Expected a channel or a file".to_string());
+        }
+        Ok(s.parse().map_or_else(|_| ChannelSource::File(s.to_string()), ChannelSource::Channel))
+    }
+}
+
+/// A 3x3 matrix mixing the red, green and blue channels of an image.
+///
+/// Each row gives the weights of the input red, green and blue channels in one output channel,
+/// so the identity matrix leaves an image unchanged.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct ChannelMixer(pub [[f64; 3]; 3]);
+
+impl ChannelMixer {
+    /// Mixes the red, green and blue `planes`, clamping results to `0..=maxval`.
+    pub fn apply(&self, planes: [&[PxVal]; 3], maxval: usize) -> [Vec<PxVal>; 3] {
+        self.0.map(|row| {
+            let mixed: Vec<f64> = (0..planes[0].len())
+                .map(|i| (0..3).map(|c| row[c] * planes[c][i] as f64).sum())
+                .collect();
+            plane_from_f64(&mixed, maxval)
+        })
+    }
+}
@@ -0,0 +1,115 @@
+////////////////////////////////
+// unit tests for channels.rs //
+////////////////////////////////
+use super::*;
+use crate::args::ManipOption;
+use crate::image::tests::img_folder;
+use crate::image::{ColorImage, GrayImage, Image, ImageManip, ImageType};
+
+fn colors() -> ColorImage {
+    ColorImage {
+        width: 3,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![10, 20, 30],
+        gpixels: vec![40, 50, 60],
+        bpixels: vec![70, 80, 250],
+    }
+}
+
+fn gray(maxval: usize, pixels: Vec<PxVal>) -> GrayImage {
+    GrayImage { width: pixels.len(), height: 1, maxval, pixels }
+}
+
+#[test]
+fn parse_sources() {
+    assert_eq!("g".parse(), Ok(Channel::Green));
+    assert_eq!("blue".parse(), Ok(Channel::Blue));
+    assert!("y".parse::<Channel>().is_err());
+    assert_eq!("r".parse(), Ok(ChannelSource::Channel(Channel::Red)));
+    assert_eq!("nir.pgm".parse(), Ok(ChannelSource::File("nir.pgm".to_string())));
+    assert!("".parse::<ChannelSource>().is_err());
+}
+
+#[test]
+fn extract_channels() {
+    let img = colors();
+    assert_eq!(img.channel(Channel::Green), gray(255, vec![40, 50, 60]));
+    assert_eq!(img.channel(Channel::Blue), gray(255, vec![70, 80, 250]));
+}
+
+#[test]
+fn combine_checks_channels() {
+    let img = ColorImage::combine(gray(15, vec![1, 2]), gray(15, vec![3, 4]), gray(15, vec![5, 6])).unwrap();
+    assert_eq!((img.maxval, img.rpixels, img.gpixels, img.bpixels), (15, vec![1, 2], vec![3, 4], vec![5, 6]));
+
+    assert!(ColorImage::combine(gray(15, vec![1, 2]), gray(255, vec![3, 4]), gray(15, vec![5, 6])).is_err());
+    assert!(ColorImage::combine(gray(15, vec![1, 2]), gray(15, vec![3, 4]), gray(15, vec![5])).is_err());
+}
+
+#[test]
+fn combine_from_files() {
+    let img = Image::load(&(img_folder() + "feep.pgm")).unwrap();
+    let op = ManipOption::Combine([
+        ChannelSource::Channel(Channel::Red),
+        ChannelSource::File(img_folder() + "feep_raw.pgm"),
+        ChannelSource::Channel(Channel::Blue),
+    ]);
+    match (img.manipulate(&op).unwrap().0, img.0) {
+        (ImageType::Color(out), ImageType::Grayscale(feep)) => {
+            assert_eq!(out.rpixels, feep.pixels);
+            assert_eq!(out.gpixels, feep.pixels);
+            assert_eq!(out.bpixels, feep.pixels);
+        }
+        _ => panic!("expected a color image from a grayscale one"),
+    }
+
+    // a different maxval
+    let op = ManipOption::Combine([
+        ChannelSource::Channel(Channel::Red),
+        ChannelSource::File(img_folder() + "feep_raw_gimp.pgm"),
+        ChannelSource::Channel(Channel::Red),
+    ]);
+    assert!(Image::load(&(img_folder() + "feep.pgm")).unwrap().manipulate(&op).is_err());
+
+    // not a grayscale image
+    let op = ManipOption::Combine([
+        ChannelSource::File(img_folder() + "feep.ppm"),
+        ChannelSource::Channel(Channel::Red),
+        ChannelSource::Channel(Channel::Red),
+    ]);
+    assert!(Image::load(&(img_folder() + "feep.pgm")).unwrap().manipulate(&op).is_err());
+}
+
+#[test]
+fn permute_channels() {
+    let out = colors().permute(&[Channel::Blue, Channel::Green, Channel::Red]);
+    assert_eq!((out.rpixels, out.gpixels, out.bpixels), (colors().bpixels, colors().gpixels, colors().rpixels));
+
+    let out = colors().permute(&[Channel::Green, Channel::Green, Channel::Green]);
+    assert_eq!(out.rpixels, out.bpixels);
+}
+
+#[test]
+fn mix_channels() {
+    let identity = ChannelMixer([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    assert_eq!(colors().mix(&identity), colors());
+
+    let mixer = ChannelMixer([[0.5, 0.5, 0.0], [0.0, 0.0, 0.0], [1.0, 0.0, 0.5]]);
+    let out = colors().mix(&mixer);
+    assert_eq!(out.rpixels, vec![25, 35, 45]);
+    assert_eq!(out.gpixels, vec![0, 0, 0]);
+    assert_eq!(out.bpixels, vec![45, 60, 155]);
+
+    // results are clamped to maxval and 0
+    let clamping = ChannelMixer([[0.0, 0.0, 2.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+    let out = colors().mix(&clamping);
+    assert_eq!(out.rpixels, vec![140, 160, 255]);
+    assert_eq!(out.gpixels, vec![0, 0, 0]);
+    let out = gray(15, vec![2, 10]).mix(&ChannelMixer([[2.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]));
+    assert_eq!((out.rpixels, out.gpixels), (vec![4, 15], vec![0, 0]));
+
+    // grayscale images are mixed as though every channel were equal
+    let out = gray(15, vec![2, 10]).mix(&mixer);
+    assert_eq!((out.rpixels, out.gpixels, out.bpixels), (vec![2, 10], vec![0, 0], vec![3, 15]));
+}
@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use super::linear;
+
+#[cfg(test)]
+mod tests;
+
+/// A color space which RGB colors can be converted to and from.
+///
+/// Conversions work on RGB values from 0 to 1, and produce components in the usual units for
+/// each space:
+///
+/// | space   | components | ranges                        |
+/// |---------|------------|-------------------------------|
+/// | `Hsv`   | h, s, v    | 0..360 degrees, 0..1, 0..1    |
+/// | `Hsl`   | h, s, l    | 0..360 degrees, 0..1, 0..1    |
+/// | `YCbCr` | y, cb, cr  | 0..1, -0.5..0.5, -0.5..0.5    |
+/// | `Lab`   | l, a, b    | 0..100, about -128..128 each  |
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ColorSpace {
+    Hsv,
+    Hsl,
+    /// Full-range BT.601 YCbCr, as used by JPEG
+    YCbCr,
+    /// CIE L*a*b* relative to the D65 white point
+    Lab,
+}
+
+/// The maximum value of a single component while an operation is applied to it.
+///
+/// Components don't line up with the image's own samples (a hue of 8 bits is far coarser than
+/// the hues of 8-bit RGB), so they're held with 16 bits of precision regardless of its `maxval`.
+pub const COMPONENT_MAXVAL: usize = 65535;
+
+/// The D65 white point in CIE XYZ
+const D65: [f64; 3] = [0.950_47, 1.0, 1.088_83];
+
+impl ColorSpace {
+    /// The names of this space's components, in order
+    pub fn components(self) -> [&'static str; 3] {
+        match self {
+            ColorSpace::Hsv => ["h", "s", "v"],
+            ColorSpace::Hsl => ["h", "s", "l"],
+            ColorSpace::YCbCr => ["y", "cb", "cr"],
+            ColorSpace::Lab => ["l", "a", "b"],
+        }
+    }
+
+    /// The `(min, max)` range of each component
+    fn ranges(self) -> [(f64, f64); 3] {
+        match self {
+            ColorSpace::Hsv | ColorSpace::Hsl => [(0.0, 360.0), (0.0, 1.0), (0.0, 1.0)],
+            ColorSpace::YCbCr => [(0.0, 1.0), (-0.5, 0.5), (-0.5, 0.5)],
+            ColorSpace::Lab => [(0.0, 100.0), (-128.0, 127.0), (-128.0, 127.0)],
+        }
+    }
+
+    /// Scales component `index` of a color from its natural range into 0..1.
+    pub fn normalize(self, index: usize, value: f64) -> f64 {
+        let (lo, hi) = self.ranges()[index];
+        ((value - lo) / (hi - lo)).clamp(0.0, 1.0)
+    }
+
+    /// Scales a value from 0..1 back into the natural range of component `index`.
+    pub fn denormalize(self, index: usize, value: f64) -> f64 {
+        let (lo, hi) = self.ranges()[index];
+        lo + value * (hi - lo)
+    }
+
+    /// Converts an RGB color (each from 0 to 1) into this space.
+    pub fn convert(self, [r, g, b]: [f64; 3]) -> [f64; 3] {
+        match self {
+            ColorSpace::Hsv => {
+                let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+                let s = if max > 0.0 { (max - min) / max } else { 0.0 };
+                [hue(r, g, b, max, min), s, max]
+            }
+            ColorSpace::Hsl => {
+                let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+                let l = (max + min) / 2.0;
+                let s = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * l - 1.0).abs()) };
+                [hue(r, g, b, max, min), s, l]
+            }
+            ColorSpace::YCbCr => {
+                let y = 0.299 * r + 0.587 * g + 0.114 * b;
+                [y, (b - y) / 1.772, (r - y) / 1.402]
+            }
+            ColorSpace::Lab => {
+                let (r, g, b) = (linear::decode(r), linear::decode(g), linear::decode(b));
+                let xyz = [
+                    0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+                    0.212_672_9 * r + 0.715_152_2 * g + 0.072_175_0 * b,
+                    0.019_333_9 * r + 0.119_192_0 * g + 0.950_304_1 * b,
+                ];
+                let f = |t: f64| {
+                    if t > 216.0 / 24389.0 { t.cbrt() } else { (24389.0 / 27.0 * t + 16.0) / 116.0 }
+                };
+                let [fx, fy, fz] = [f(xyz[0] / D65[0]), f(xyz[1] / D65[1]), f(xyz[2] / D65[2])];
+                [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+            }
+        }
+    }
+
+    /// Converts a color in this space back into RGB (each from 0 to 1, clamped).
+    pub fn to_rgb(self, [c0, c1, c2]: [f64; 3]) -> [f64; 3] {
+        let rgb = match self {
+            ColorSpace::Hsv => {
+                let chroma = c2 * c1;
+                from_hue(c0, chroma, c2 - chroma)
+            }
+            ColorSpace::Hsl => {
+                let chroma = (1.0 - (2.0 * c2 - 1.0).abs()) * c1;
+                from_hue(c0, chroma, c2 - chroma / 2.0)
+            }
+            ColorSpace::YCbCr => {
+                let r = c0 + 1.402 * c2;
+                let b = c0 + 1.772 * c1;
+                let g = (c0 - 0.299 * r - 0.114 * b) / 0.587;
+                [r, g, b]
+            }
+            ColorSpace::Lab => {
+                let fy = (c0 + 16.0) / 116.0;
+                let (fx, fz) = (fy + c1 / 500.0, fy - c2 / 200.0);
+                let f_inv = |f: f64| {
+                    if f > 6.0 / 29.0 { f * f * f } else { (116.0 * f - 16.0) * 27.0 / 24389.0 }
+                };
+                let [x, y, z] = [f_inv(fx) * D65[0], f_inv(fy) * D65[1], f_inv(fz) * D65[2]];
+                let rgb = [
+                     3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+                    -0.969_266_0 * x + 1.876_010_8 * y + 0.041_556_0 * z,
+                     0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+                ];
+                [
+                    linear::encode(rgb[0].clamp(0.0, 1.0)),
+                    linear::encode(rgb[1].clamp(0.0, 1.0)),
+                    linear::encode(rgb[2].clamp(0.0, 1.0)),
+                ]
+            }
+        };
+        [rgb[0].clamp(0.0, 1.0), rgb[1].clamp(0.0, 1.0), rgb[2].clamp(0.0, 1.0)]
+    }
+}
+
+/// The hue (in degrees) shared by HSV and HSL
+fn hue(r: f64, g: f64, b: f64, max: f64, min: f64) -> f64 {
+    let chroma = max - min;
+    if chroma == 0.0 {
+        return 0.0;
+    }
+    let sector = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    60.0 * sector
+}
+
+/// Rebuilds RGB from a hue (in degrees), a chroma, and the amount `m` added to every channel
+fn from_hue(h: f64, chroma: f64, m: f64) -> [f64; 3] {
+    let sector = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+impl FromStr for ColorSpace {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hsv" => Ok(ColorSpace::Hsv),
+            "hsl" => Ok(ColorSpace::Hsl),
+            "ycbcr" => Ok(ColorSpace::YCbCr),
+            "lab" => Ok(ColorSpace::Lab),
+            other => Err(format!("Unknown color space '{}'", other)),
+        }
+    }
+}
+
+/// Parses a single component of a color space, e.g. `hsv,s` or `lab,l`.
+pub fn parse_component(s: &str) -> Result<(ColorSpace, usize), String> {
+    let (space, component) = s.split_once(',')
+        .ok_or_else(|| format!("Expected 'space,component' (got '{}')", s))?;
+    let space: ColorSpace = space.parse()?;
+    match space.components().iter().position(|&c| c == component) {
+        Some(index) => Ok((space, index)),
+        None => Err(format!(
+            "Expected one of {:?} for component (got '{}')",
+            space.components(),
+            component
+        )),
+    }
+}
@@ -0,0 +1,132 @@
+//////////////////////////////////
+// unit tests for colorspace.rs //
+//////////////////////////////////
+use super::*;
+use crate::args::ManipOption;
+use crate::image::{ColorImage, GrayImage, Image, ImageManip, ImageType};
+
+const SPACES: [ColorSpace; 4] = [ColorSpace::Hsv, ColorSpace::Hsl, ColorSpace::YCbCr, ColorSpace::Lab];
+
+fn assert_close(a: [f64; 3], b: [f64; 3], tolerance: f64) {
+    for i in 0..3 {
+        assert!((a[i] - b[i]).abs() < tolerance, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn round_trips() {
+    for &space in &SPACES {
+        for r in 0..=4 {
+            for g in 0..=4 {
+                for b in 0..=4 {
+                    let rgb = [r as f64 / 4.0, g as f64 / 4.0, b as f64 / 4.0];
+                    assert_close(space.to_rgb(space.convert(rgb)), rgb, 1e-6);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn hsv_and_hsl() {
+    assert_close(ColorSpace::Hsv.convert([1.0, 0.0, 0.0]), [0.0, 1.0, 1.0], 1e-12);
+    assert_close(ColorSpace::Hsv.convert([0.0, 0.5, 0.5]), [180.0, 1.0, 0.5], 1e-12);
+    assert_close(ColorSpace::Hsl.convert([0.0, 0.5, 0.5]), [180.0, 1.0, 0.25], 1e-12);
+    assert_close(ColorSpace::Hsl.convert([1.0, 0.5, 1.0]), [300.0, 1.0, 0.75], 1e-12);
+    assert_close(ColorSpace::Hsl.convert([0.5, 0.5, 0.5]), [0.0, 0.0, 0.5], 1e-12);
+}
+
+#[test]
+fn ycbcr() {
+    assert_close(ColorSpace::YCbCr.convert([1.0, 1.0, 1.0]), [1.0, 0.0, 0.0], 1e-12);
+    assert_close(ColorSpace::YCbCr.convert([0.0, 0.0, 1.0]), [0.114, 0.5, -0.0813], 1e-3);
+}
+
+#[test]
+fn lab_reference_colors() {
+    assert_close(ColorSpace::Lab.convert([1.0, 1.0, 1.0]), [100.0, 0.0, 0.0], 1e-3);
+    assert_close(ColorSpace::Lab.convert([0.0, 0.0, 0.0]), [0.0, 0.0, 0.0], 1e-9);
+    // pure red, per the usual sRGB/D65 tables
+    assert_close(ColorSpace::Lab.convert([1.0, 0.0, 0.0]), [53.24, 80.09, 67.20], 0.05);
+}
+
+#[test]
+fn normalized_components() {
+    assert_eq!(ColorSpace::Hsv.normalize(0, 180.0), 0.5);
+    assert_eq!(ColorSpace::YCbCr.normalize(1, 0.0), 0.5);
+    assert_eq!(ColorSpace::Lab.normalize(0, 150.0), 1.0);
+    for &space in &SPACES {
+        for i in 0..3 {
+            assert!((space.normalize(i, space.denormalize(i, 0.25)) - 0.25).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn parse_components() {
+    assert_eq!(parse_component("hsv,s"), Ok((ColorSpace::Hsv, 1)));
+    assert_eq!(parse_component("ycbcr,cr"), Ok((ColorSpace::YCbCr, 2)));
+    assert_eq!(parse_component("lab,l"), Ok((ColorSpace::Lab, 0)));
+    assert!(parse_component("lab,h").is_err());
+    assert!(parse_component("cmyk,k").is_err());
+    assert!(parse_component("hsv").is_err());
+}
+
+fn colors() -> ColorImage {
+    ColorImage {
+        width: 2,
+        height: 2,
+        maxval: 255,
+        rpixels: vec![255, 0, 0, 40],
+        gpixels: vec![0, 255, 128, 80],
+        bpixels: vec![0, 0, 255, 200],
+    }
+}
+
+#[test]
+fn component_images() {
+    let img = colors();
+    assert_eq!(img.component(ColorSpace::Hsv, 2).pixels, vec![255, 255, 255, 200]);
+    assert_eq!(img.component(ColorSpace::Hsv, 1).pixels, vec![255, 255, 255, 204]);
+    // hues of 0, 120, 210 and 225 degrees, as a fraction of 360
+    assert_eq!(img.component(ColorSpace::Hsl, 0).pixels, vec![0, 85, 149, 159]);
+
+    let gray = GrayImage { width: 2, height: 1, maxval: 15, pixels: vec![0, 15] };
+    let y = gray.component(ColorSpace::YCbCr, 0);
+    assert_eq!((y.maxval, y.pixels), (15, vec![0, 15]));
+    assert_eq!(gray.component(ColorSpace::Lab, 1).pixels, vec![8, 8]);
+}
+
+#[test]
+fn do_nothing_to_a_component() {
+    let img = Image::from(colors());
+    for &space in &SPACES {
+        let op = ManipOption::InComponent { space, index: 0, op: Box::new(ManipOption::DoNothing) };
+        assert_eq!(img.manipulate(&op).unwrap(), img);
+    }
+}
+
+#[test]
+fn manipulate_one_component() {
+    // halving the value of every pixel leaves hue and saturation alone
+    let img = Image::from(colors());
+    let curve = crate::image::ToneCurve::Levels {
+        black: 0.0, white: 1.0, gamma: 1.0, out_black: 0.0, out_white: 0.5
+    };
+    let op = ManipOption::InComponent { space: ColorSpace::Hsv, index: 2, op: Box::new(ManipOption::Tone(curve)) };
+    match img.manipulate(&op).unwrap().0 {
+        ImageType::Color(out) => {
+            assert_eq!(out.rpixels, vec![128, 0, 0, 20]);
+            assert_eq!(out.gpixels, vec![0, 128, 64, 40]);
+            assert_eq!(out.bpixels, vec![0, 0, 128, 100]);
+        }
+        _ => panic!("expected a color image"),
+    }
+}
+
+#[test]
+fn component_operations_must_keep_size() {
+    let img = Image::from(colors());
+    let op = ManipOption::InComponent { space: ColorSpace::Lab, index: 0, op: Box::new(ManipOption::Downscale(2)) };
+    assert!(img.manipulate(&op).is_err());
+}
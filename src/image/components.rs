@@ -0,0 +1,226 @@
+use std::str::FromStr;
+
+use super::colorspace::ColorSpace;
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// Which neighbors of a pixel count as touching it.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Connectivity {
+    /// Only the pixels above, below, left and right
+    Four,
+    /// The diagonal pixels as well
+    Eight,
+}
+
+impl FromStr for Connectivity {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(Connectivity::Four),
+            "8" => Ok(Connectivity::Eight),
+            other => Err(format!("Expected a connectivity of '4' or '8' (got '{}')", other)),
+        }
+    }
+}
+
+/// The measurements of one connected component.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Blob {
+    /// Components are numbered from 1, in the order a raster scan first reaches them
+    pub label: usize,
+    /// The number of pixels in the component
+    pub area: usize,
+    /// `(left, top, right, bottom)`, inclusive
+    pub bounds: (usize, usize, usize, usize),
+    /// The mean `(x, y)` of the component's pixels
+    pub centroid: (f64, f64),
+}
+
+/// The connected components of an image's foreground, as a label for every pixel.
+pub struct Labels {
+    width: usize,
+    /// `0` for the background, otherwise the component's label
+    labels: Vec<usize>,
+    count: usize,
+}
+
+/// Every component of an image along with its measurements, ready to print.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct BlobReport {
+    pub width: usize,
+    pub height: usize,
+    pub connectivity: Connectivity,
+    pub blobs: Vec<Blob>,
+}
+
+/// Finds the root of a provisional label, flattening the path to it along the way.
+fn find(parent: &mut [usize], mut label: usize) -> usize {
+    while parent[label] != label {
+        parent[label] = parent[parent[label]];
+        label = parent[label];
+    }
+    label
+}
+
+impl Labels {
+    /// Labels the connected components of the `true` pixels of a `width` by `height` mask.
+    ///
+    /// This is the classic two-pass algorithm: the first pass gives every pixel the smallest
+    /// label among its already-visited neighbors (or a new one), recording which labels meet in
+    /// a union-find forest, and the second replaces every label with its final number.
+    pub fn new(foreground: &[bool], width: usize, height: usize, connectivity: Connectivity) -> Labels {
+        let mut labels = vec![0; foreground.len()];
+        // label 0 is the background, so the forest starts with it as its own root
+        let mut parent = vec![0];
+
+        for y in 0..height {
+            for x in 0..width {
+                if !foreground[y * width + x] {
+                    continue;
+                }
+
+                let mut neighbors = Vec::with_capacity(4);
+                if x > 0 {
+                    neighbors.push(labels[y * width + x - 1]);
+                }
+                if y > 0 {
+                    let above = (y - 1) * width;
+                    neighbors.push(labels[above + x]);
+                    if let Connectivity::Eight = connectivity {
+                        if x > 0 {
+                            neighbors.push(labels[above + x - 1]);
+                        }
+                        if x + 1 < width {
+                            neighbors.push(labels[above + x + 1]);
+                        }
+                    }
+                }
+
+                let roots: Vec<usize> = neighbors.into_iter()
+                    .filter(|&l| l != 0)
+                    .map(|l| find(&mut parent, l))
+                    .collect();
+                labels[y * width + x] = match roots.iter().min() {
+                    Some(&root) => {
+                        for &other in &roots {
+                            parent[other] = root;
+                        }
+                        root
+                    }
+                    None => {
+                        parent.push(parent.len());
+                        parent.len() - 1
+                    }
+                };
+            }
+        }
+
+        // number the components in the order their first pixels appear
+        let mut numbers = vec![0; parent.len()];
+        let mut count = 0;
+        for label in labels.iter_mut().filter(|l| **l != 0) {
+            let root = find(&mut parent, *label);
+            if numbers[root] == 0 {
+                count += 1;
+                numbers[root] = count;
+            }
+            *label = numbers[root];
+        }
+
+        Labels { width, labels, count }
+    }
+
+    /// Measures every component, in label order.
+    pub fn blobs(&self) -> Vec<Blob> {
+        let mut blobs: Vec<Blob> = (1..=self.count)
+            .map(|label| Blob {
+                label,
+                area: 0,
+                bounds: (usize::MAX, usize::MAX, 0, 0),
+                centroid: (0.0, 0.0),
+            })
+            .collect();
+
+        for (i, &label) in self.labels.iter().enumerate().filter(|(_, &l)| l != 0) {
+            let (x, y) = (i % self.width, i / self.width);
+            let blob = &mut blobs[label - 1];
+            let (left, top, right, bottom) = blob.bounds;
+            blob.area += 1;
+            blob.bounds = (left.min(x), top.min(y), right.max(x), bottom.max(y));
+            blob.centroid.0 += x as f64;
+            blob.centroid.1 += y as f64;
+        }
+        for blob in &mut blobs {
+            blob.centroid.0 /= blob.area as f64;
+            blob.centroid.1 /= blob.area as f64;
+        }
+        blobs
+    }
+
+    /// Paints every component in its own color on a black background, as 8-bit RGB planes.
+    ///
+    /// Hues step around the color wheel by the golden angle, so neighboring labels get very
+    /// different colors and no hue ever exactly repeats; the brightness alternates as well.
+    pub fn colorize(&self) -> [Vec<PxVal>; 3] {
+        let palette: Vec<[PxVal; 3]> = (0..=self.count)
+            .map(|label| {
+                if label == 0 {
+                    return [0; 3];
+                }
+                let hue = ((label - 1) as f64 * 137.507_764) % 360.0;
+                let value = if label % 2 == 1 { 1.0 } else { 0.7 };
+                let rgb = ColorSpace::Hsv.to_rgb([hue, 0.85, value]);
+                [0, 1, 2].map(|c| (rgb[c] * 255.0).round() as PxVal)
+            })
+            .collect();
+
+        let plane = |c: usize| self.labels.iter().map(|&l| palette[l][c]).collect();
+        [plane(0), plane(1), plane(2)]
+    }
+}
+
+impl BlobReport {
+    /// Formats the report as human-readable text, one line per component.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}x{}, {} components ({}-connected)\n",
+            self.width, self.height, self.blobs.len(), self.connectivity_number()
+        );
+        for b in &self.blobs {
+            let (left, top, right, bottom) = b.bounds;
+            text += &format!(
+                "{}: area {} bounds {},{} to {},{} centroid {:.3},{:.3}\n",
+                b.label, b.area, left, top, right, bottom, b.centroid.0, b.centroid.1
+            );
+        }
+        text
+    }
+
+    /// Formats the report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let blobs: Vec<String> = self.blobs.iter()
+            .map(|b| {
+                let (left, top, right, bottom) = b.bounds;
+                format!(
+                    "{{\"label\":{},\"area\":{},\"bounds\":{{\"left\":{},\"top\":{},\"right\":{},\"bottom\":{}}},\"centroid\":{{\"x\":{},\"y\":{}}}}}",
+                    b.label, b.area, left, top, right, bottom, b.centroid.0, b.centroid.1
+                )
+            })
+            .collect();
+        format!(
+            "{{\"width\":{},\"height\":{},\"connectivity\":{},\"count\":{},\"components\":[{}]}}\n",
+            self.width, self.height, self.connectivity_number(), self.blobs.len(), blobs.join(",")
+        )
+    }
+
+    fn connectivity_number(&self) -> usize {
+        match self.connectivity {
+            Connectivity::Four => 4,
+            Connectivity::Eight => 8,
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//////////////////////////////////
+// unit tests for components.rs //
+//////////////////////////////////
+use super::*;
+use crate::image::{ColorImage, GrayImage, Image, ImageManip, ImageType};
+
+fn mask(pixels: &[u8]) -> Vec<bool> {
+    pixels.iter().map(|&p| p != 0).collect()
+}
+
+#[test]
+fn diagonals_need_eight_connectivity() {
+    let fg = mask(&[
+        1, 0, 0,
+        0, 1, 0,
+        0, 0, 1,
+    ]);
+    let four = Labels::new(&fg, 3, 3, Connectivity::Four);
+    assert_eq!(four.count, 3);
+    assert_eq!(four.labels, vec![1, 0, 0, 0, 2, 0, 0, 0, 3]);
+
+    let eight = Labels::new(&fg, 3, 3, Connectivity::Eight);
+    assert_eq!(eight.count, 1);
+    assert_eq!(eight.labels, vec![1, 0, 0, 0, 1, 0, 0, 0, 1]);
+}
+
+#[test]
+fn merges_labels_which_meet_later() {
+    // the arms of the U and the W get different provisional labels until their bottoms join
+    let fg = mask(&[
+        1, 0, 1, 0, 1, 0, 1, 0, 1,
+        1, 0, 1, 0, 1, 0, 1, 0, 1,
+        1, 1, 1, 0, 1, 1, 1, 1, 1,
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 1, 1, 0, 0, 0, 0, 0, 1,
+    ]);
+    let labels = Labels::new(&fg, 9, 5, Connectivity::Four);
+    assert_eq!(labels.count, 4);
+    assert_eq!(labels.labels[..9], [1, 0, 1, 0, 2, 0, 2, 0, 2]);
+    assert_eq!(labels.labels[36..], [0, 3, 3, 0, 0, 0, 0, 0, 4]);
+}
+
+#[test]
+fn measures_blobs() {
+    let fg = mask(&[
+        0, 1, 1, 0, 0,
+        0, 1, 1, 0, 1,
+        0, 0, 1, 0, 0,
+    ]);
+    let blobs = Labels::new(&fg, 5, 3, Connectivity::Eight).blobs();
+    assert_eq!(blobs, vec![
+        Blob { label: 1, area: 5, bounds: (1, 0, 2, 2), centroid: (1.6, 0.8) },
+        Blob { label: 2, area: 1, bounds: (4, 1, 4, 1), centroid: (4.0, 1.0) },
+    ]);
+    assert!(Labels::new(&[false; 4], 2, 2, Connectivity::Four).blobs().is_empty());
+}
+
+#[test]
+fn colorize_components() {
+    let labels = Labels::new(&mask(&[1, 0, 1, 0, 1, 1]), 6, 1, Connectivity::Four);
+    let [r, g, b] = labels.colorize();
+    let colors: Vec<_> = (0..6).map(|i| (r[i], g[i], b[i])).collect();
+
+    assert_eq!(colors[1], (0, 0, 0));
+    assert_eq!(colors[3], (0, 0, 0));
+    assert_eq!(colors[4], colors[5]);
+    assert_ne!(colors[0], colors[2]);
+    assert_ne!(colors[2], colors[4]);
+    assert_ne!(colors[0], colors[4]);
+    assert!(colors.iter().all(|&(r, g, b)| r <= 255 && g <= 255 && b <= 255));
+}
+
+#[test]
+fn label_color_image() {
+    // any channel being lit makes a pixel foreground
+    let img = ColorImage {
+        width: 3,
+        height: 1,
+        maxval: 15,
+        rpixels: vec![0, 0, 0],
+        gpixels: vec![3, 0, 0],
+        bpixels: vec![0, 0, 9],
+    };
+    let out = img.label(Connectivity::Eight);
+    assert_eq!(out.maxval, 255);
+    assert_eq!((out.rpixels[1], out.gpixels[1], out.bpixels[1]), (0, 0, 0));
+    assert_ne!((out.rpixels[0], out.gpixels[0]), (out.rpixels[2], out.gpixels[2]));
+}
+
+#[test]
+fn bilevel_foreground_is_black() {
+    // thresholded text is black on white, and it's the text that should be counted
+    let text = GrayImage { width: 4, height: 1, maxval: 1, pixels: vec![0, 1, 1, 0] };
+    assert_eq!(text.foreground(), vec![true, false, false, true]);
+    assert_eq!(Image::from(text).blobs(Connectivity::Eight).blobs.len(), 2);
+
+    // but deeper images count everything that isn't black
+    let gray = GrayImage { width: 4, height: 1, maxval: 255, pixels: vec![0, 1, 1, 0] };
+    assert_eq!(gray.foreground(), vec![false, true, true, false]);
+}
+
+#[test]
+fn report() {
+    let img = Image(ImageType::Grayscale(GrayImage {
+        width: 4,
+        height: 2,
+        maxval: 1,
+        pixels: vec![
+            0, 0, 1, 1,
+            1, 1, 0, 1,
+        ],
+    }));
+
+    let eight = img.blobs(Connectivity::Eight);
+    assert_eq!(eight.to_text(), "4x2, 1 components (8-connected)\n1: area 3 bounds 0,0 to 2,1 centroid 1.000,0.333\n");
+
+    let four = img.blobs(Connectivity::Four);
+    assert_eq!(
+        four.to_json(),
+        "{\"width\":4,\"height\":2,\"connectivity\":4,\"count\":2,\"components\":[\
+         {\"label\":1,\"area\":2,\"bounds\":{\"left\":0,\"top\":0,\"right\":1,\"bottom\":0},\"centroid\":{\"x\":0.5,\"y\":0}},\
+         {\"label\":2,\"area\":1,\"bounds\":{\"left\":2,\"top\":1,\"right\":2,\"bottom\":1},\"centroid\":{\"x\":2,\"y\":1}}]}\n"
+    );
+}
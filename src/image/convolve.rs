@@ -0,0 +1,347 @@
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use super::{plane_from_f64, plane_to_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// How to sample neighbors which fall off the edge of an image.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum EdgeMode {
+    /// Use the nearest pixel on the edge
+    Replicate,
+    /// Use the value at the center of the kernel (what the README specifies for sharpen/smooth)
+    Center,
+    /// Treat everything beyond the edge as zero
+    Zero,
+    /// Mirror the image about its outermost pixels (`dcb|abcd|cba`)
+    Reflect,
+    /// Wrap around to the opposite side of the image
+    Wrap,
+}
+
+impl EdgeMode {
+    /// Maps a possibly out-of-bounds coordinate `i` onto `0..len`.
+    ///
+    /// Returns `None` when the sample should be treated as zero, or for [`EdgeMode::Center`], when
+    /// it should be replaced by the center value (which can't be found one axis at a time).
+    pub fn resolve(self, i: isize, len: usize) -> Option<usize> {
+        let n = len as isize;
+        if i >= 0 && i < n {
+            return Some(i as usize);
+        }
+
+        match self {
+            EdgeMode::Replicate => Some(i.max(0).min(n - 1) as usize),
+            EdgeMode::Zero | EdgeMode::Center => None,
+            EdgeMode::Reflect => {
+                if n == 1 {
+                    return Some(0);
+                }
+                // a reflected image repeats with a period of 2*(n-1)
+                let period = 2 * (n - 1);
+                let j = i.rem_euclid(period);
+                Some(if j < n { j } else { period - j } as usize)
+            }
+            EdgeMode::Wrap => Some(i.rem_euclid(n) as usize),
+        }
+    }
+}
+
+impl FromStr for EdgeMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "replicate" => Ok(EdgeMode::Replicate),
+            "center" => Ok(EdgeMode::Center),
+            "zero" => Ok(EdgeMode::Zero),
+            "reflect" => Ok(EdgeMode::Reflect),
+            "wrap" => Ok(EdgeMode::Wrap),
+            other => Err(format!("Unknown edge mode '{}'", other)),
+        }
+    }
+}
+
+/// A rectangular, odd-sized convolution kernel.
+///
+/// Each output value is computed as `sum(weight * neighbor) / divisor + bias`, where the kernel is
+/// centered on the value being computed.
+#[cfg_attr(test, derive(Debug, Clone, PartialEq))]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    weights: Vec<f64>,
+    divisor: f64,
+    bias: f64,
+    edge: EdgeMode,
+    /// Row and column vectors whose outer product equals `weights`, if such a pair exists
+    separable: Option<(Vec<f64>, Vec<f64>)>,
+}
+
+impl Kernel {
+    /// Creates a kernel from `weights` given in row-major order.
+    ///
+    /// The divisor defaults to the sum of the weights (or 1, if they sum to zero), the bias to 0,
+    /// and the edge mode to [`EdgeMode::Replicate`].
+    pub fn new(width: usize, height: usize, weights: Vec<f64>) -> io::Result<Kernel> {
+        if width.is_multiple_of(2) || height.is_multiple_of(2) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Kernel dimensions must be odd (got {}x{})", width, height)
+            ));
+        }
+        if weights.len() != width * height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Inconsistent kernel size '{}' (expected '{}')",
+                    weights.len(),
+                    width * height
+                )
+            ));
+        }
+
+        let sum: f64 = weights.iter().sum();
+        let separable = Kernel::factor(width, height, &weights);
+
+        Ok(Kernel {
+            width,
+            height,
+            weights,
+            divisor: if sum == 0.0 { 1.0 } else { sum },
+            bias: 0.0,
+            edge: EdgeMode::Replicate,
+            separable,
+        })
+    }
+
+    /// Creates a kernel from the outer product of a `row` and a `column` vector.
+    ///
+    /// Kernels made this way are always applied with the separable fast path.
+    pub fn separable(row: Vec<f64>, column: Vec<f64>) -> io::Result<Kernel> {
+        let weights = column.iter()
+            .flat_map(|c| row.iter().map(move |r| r * c))
+            .collect();
+        let mut kernel = Kernel::new(row.len(), column.len(), weights)?;
+        kernel.separable = Some((row, column));
+        Ok(kernel)
+    }
+
+    /// The README's sharpen operation: `5*v - N - S - E - W`
+    pub fn sharpen() -> Kernel {
+        Kernel::new(3, 3, vec![
+             0.0, -1.0,  0.0,
+            -1.0,  5.0, -1.0,
+             0.0, -1.0,  0.0,
+        ]).unwrap().with_edge(EdgeMode::Center)
+    }
+
+    /// The README's smooth operation: an unweighted average of a 3x3 neighborhood
+    pub fn smooth() -> Kernel {
+        Kernel::new(3, 3, vec![1.0; 9]).unwrap().with_edge(EdgeMode::Center)
+    }
+
+    /// Reads a kernel from the text file located at `path`.
+    ///
+    /// See the [`FromStr`] implementation for the format.
+    pub fn load(path: &str) -> io::Result<Kernel> {
+        let text = fs::read_to_string(path)?;
+        text.parse()
+            .map_err(|e: String| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn with_divisor(mut self, divisor: f64) -> Kernel {
+        self.divisor = divisor;
+        self
+    }
+
+    pub fn with_bias(mut self, bias: f64) -> Kernel {
+        self.bias = bias;
+        self
+    }
+
+    pub fn with_edge(mut self, edge: EdgeMode) -> Kernel {
+        self.edge = edge;
+        self
+    }
+
+    /// Attempts to decompose `weights` into a row and a column vector.
+    ///
+    /// This works by picking the largest-magnitude weight as a pivot, taking its row as the row
+    /// vector and its (normalized) column as the column vector, and then checking that their
+    /// outer product reproduces every weight.
+    fn factor(width: usize, height: usize, weights: &[f64]) -> Option<(Vec<f64>, Vec<f64>)> {
+        let (pivot, &largest) = weights.iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().partial_cmp(&b.1.abs()).unwrap())?;
+        if largest == 0.0 {
+            return None;
+        }
+
+        let (py, px) = (pivot / width, pivot % width);
+        let row: Vec<f64> = weights[py * width..(py + 1) * width].to_vec();
+        let column: Vec<f64> = (0..height)
+            .map(|y| weights[y * width + px] / largest)
+            .collect();
+
+        for y in 0..height {
+            for x in 0..width {
+                if (column[y] * row[x] - weights[y * width + x]).abs() > 1e-9 {
+                    return None;
+                }
+            }
+        }
+
+        Some((row, column))
+    }
+
+    /// Convolves a single plane of `width`x`height` values, returning unrounded results.
+    ///
+    /// The separable fast path isn't used with [`EdgeMode::Center`], since a neighbor off the
+    /// edge depends on both coordinates of the center.
+    pub fn convolve(&self, plane: &[f64], width: usize, height: usize) -> Vec<f64> {
+        let sums = match &self.separable {
+            Some((row, column)) if self.edge != EdgeMode::Center => {
+                let tmp = self.pass(plane, width, height, row, true);
+                self.pass(&tmp, width, height, column, false)
+            }
+            _ => self.full(plane, width, height),
+        };
+
+        sums.into_iter()
+            .map(|sum| sum / self.divisor + self.bias)
+            .collect()
+    }
+
+    /// Convolves a single plane of pixels, clamping results to `0..=maxval`.
+    pub fn apply(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        let plane = plane_to_f64(pixels);
+        plane_from_f64(&self.convolve(&plane, width, height), maxval)
+    }
+
+    /// One dimension of the separable fast path
+    fn pass(&self, plane: &[f64], width: usize, height: usize, taps: &[f64], horizontal: bool) -> Vec<f64> {
+        let r = (taps.len() / 2) as isize;
+        let mut out = Vec::with_capacity(plane.len());
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for (k, tap) in taps.iter().enumerate() {
+                    let off = k as isize - r;
+                    let idx = if horizontal {
+                        self.edge.resolve(x as isize + off, width).map(|sx| y * width + sx)
+                    } else {
+                        self.edge.resolve(y as isize + off, height).map(|sy| sy * width + x)
+                    };
+                    if let Some(i) = idx {
+                        sum += tap * plane[i];
+                    }
+                }
+                out.push(sum);
+            }
+        }
+        out
+    }
+
+    /// The general (non-separable) convolution
+    fn full(&self, plane: &[f64], width: usize, height: usize) -> Vec<f64> {
+        let (rx, ry) = ((self.width / 2) as isize, (self.height / 2) as isize);
+        let mut out = Vec::with_capacity(plane.len());
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for ky in 0..self.height {
+                    let sy = self.edge.resolve(y as isize + ky as isize - ry, height);
+                    for kx in 0..self.width {
+                        let sx = self.edge.resolve(x as isize + kx as isize - rx, width);
+                        let value = match (sy, sx) {
+                            (Some(sy), Some(sx)) => plane[sy * width + sx],
+                            _ if self.edge == EdgeMode::Center => plane[y * width + x],
+                            _ => continue,
+                        };
+                        sum += self.weights[ky * self.width + kx] * value;
+                    }
+                }
+                out.push(sum);
+            }
+        }
+        out
+    }
+}
+
+impl FromStr for Kernel {
+    type Err = String;
+
+    /// Parses a kernel from text.
+    ///
+    /// Each non-empty line is either a comment (starting with `#`), a directive, or a row of
+    /// whitespace-separated weights. All rows must have the same (odd) length. The directives are:
+    ///
+    /// - `divisor <number>` (defaults to the sum of the weights, or 1 if they sum to zero)
+    /// - `bias <number>` (defaults to 0)
+    /// - `edge replicate|center|zero|reflect|wrap` (defaults to `replicate`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        let mut divisor = None;
+        let mut bias = None;
+        let mut edge = None;
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let first = words.next().unwrap();
+            let number = |w: Option<&str>| -> Result<f64, String> {
+                w.and_then(|w| w.parse::<f64>().ok())
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| format!("Expected a number after '{}'", first))
+            };
+            match first {
+                "divisor" => divisor = Some(number(words.next())?),
+                "bias" => bias = Some(number(words.next())?),
+                "edge" => edge = Some(
+                    words.next()
+                        .ok_or_else(|| "Expected an edge mode after 'edge'".to_string())?
+                        .parse::<EdgeMode>()?
+                ),
+                _ => {
+                    let row = line.split_whitespace()
+                        .map(|w| w.parse::<f64>()
+                            .ok()
+                            .filter(|v| v.is_finite())
+                            .ok_or_else(|| format!("Kernel contains non-numeric value '{}'", w)))
+                        .collect::<Result<Vec<f64>, String>>()?;
+                    rows.push(row);
+                }
+            }
+        }
+
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        if rows.iter().any(|r| r.len() != width) {
+            return Err("Kernel rows have inconsistent lengths".to_string());
+        }
+
+        let height = rows.len();
+        let weights = rows.into_iter().flatten().collect();
+        let mut kernel = Kernel::new(width, height, weights).map_err(|e| e.to_string())?;
+        if let Some(d) = divisor {
+            if d == 0.0 {
+                return Err("Kernel divisor must be non-zero".to_string());
+            }
+            kernel = kernel.with_divisor(d);
+        }
+        if let Some(b) = bias {
+            kernel = kernel.with_bias(b);
+        }
+        if let Some(e) = edge {
+            kernel = kernel.with_edge(e);
+        }
+        Ok(kernel)
+    }
+}
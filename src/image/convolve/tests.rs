@@ -0,0 +1,125 @@
+////////////////////////////////
+// unit tests for convolve.rs //
+////////////////////////////////
+use super::*;
+
+fn ramp() -> Vec<f64> {
+    // 4x3 plane with a distinct value everywhere
+    (0..12).map(|v| v as f64).collect()
+}
+
+#[test]
+fn edge_modes_resolve() {
+    assert_eq!(EdgeMode::Replicate.resolve(-2, 4), Some(0));
+    assert_eq!(EdgeMode::Replicate.resolve(5, 4), Some(3));
+    assert_eq!(EdgeMode::Zero.resolve(-1, 4), None);
+    assert_eq!(EdgeMode::Zero.resolve(2, 4), Some(2));
+    assert_eq!(EdgeMode::Center.resolve(-1, 4), None);
+    assert_eq!(EdgeMode::Center.resolve(3, 4), Some(3));
+    assert_eq!(EdgeMode::Reflect.resolve(-1, 4), Some(1));
+    assert_eq!(EdgeMode::Reflect.resolve(-3, 4), Some(3));
+    assert_eq!(EdgeMode::Reflect.resolve(4, 4), Some(2));
+    assert_eq!(EdgeMode::Reflect.resolve(-1, 1), Some(0));
+    assert_eq!(EdgeMode::Wrap.resolve(-1, 4), Some(3));
+    assert_eq!(EdgeMode::Wrap.resolve(9, 4), Some(1));
+}
+
+#[test]
+fn even_kernel_rejected() {
+    assert!(Kernel::new(2, 3, vec![1.0; 6]).is_err());
+    assert!(Kernel::new(3, 3, vec![1.0; 8]).is_err());
+}
+
+#[test]
+fn identity_kernel() {
+    let kernel = Kernel::new(3, 3, vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]).unwrap();
+    for edge in &[EdgeMode::Replicate, EdgeMode::Center, EdgeMode::Zero, EdgeMode::Reflect, EdgeMode::Wrap] {
+        let kernel = kernel.clone().with_edge(*edge);
+        assert_eq!(kernel.convolve(&ramp(), 4, 3), ramp());
+    }
+}
+
+#[test]
+fn separable_detected() {
+    let gauss = Kernel::new(3, 3, vec![1.0, 2.0, 1.0, 2.0, 4.0, 2.0, 1.0, 2.0, 1.0]).unwrap();
+    assert!(gauss.separable.is_some());
+    assert_eq!(gauss.divisor, 16.0);
+    assert!(Kernel::sharpen().separable.is_none());
+}
+
+#[test]
+fn separable_matches_full() {
+    let weights = vec![1.0, 2.0, 1.0, 0.0, 0.0, 0.0, -1.0, -2.0, -1.0];
+    for edge in &[EdgeMode::Replicate, EdgeMode::Zero, EdgeMode::Reflect, EdgeMode::Wrap] {
+        let fast = Kernel::new(3, 3, weights.clone()).unwrap().with_edge(*edge);
+        let mut slow = fast.clone();
+        slow.separable = None;
+        assert!(fast.separable.is_some());
+        assert_eq!(fast.convolve(&ramp(), 4, 3), slow.convolve(&ramp(), 4, 3));
+    }
+}
+
+#[test]
+fn sharpen_uses_center_at_edges() {
+    // a constant image is unchanged by sharpen, only if off-edge neighbors take some edge value
+    let pixels = vec![7; 12];
+    assert_eq!(Kernel::sharpen().apply(&pixels, 4, 3, 15), pixels);
+
+    let zeroed = Kernel::sharpen().with_edge(EdgeMode::Zero).apply(&pixels, 4, 3, 15);
+    assert_eq!(zeroed[0], 15); // 5*7 - 7 - 7 clamped to maxval
+    assert_eq!(zeroed[5], 7);
+}
+
+#[test]
+fn smooth_averages_neighborhood() {
+    let pixels = vec![
+        0, 0, 0,
+        0, 9, 0,
+        0, 0, 0,
+    ];
+    assert_eq!(Kernel::smooth().apply(&pixels, 3, 3, 255), vec![1; 9]);
+}
+
+#[test]
+fn smooth_uses_center_at_edges() {
+    // off the top edge, NW/N/NE are all the center value (not W/v/E, as replicating would give)
+    let pixels = vec![
+        0, 9, 0,
+        9, 9, 9,
+        0, 9, 0,
+    ];
+    let smoothed = Kernel::smooth().apply(&pixels, 3, 3, 255);
+    assert_eq!(smoothed[1], 7); // (3*9 + 0 + 9 + 0 + 9 + 9 + 9) / 9
+    assert_eq!(smoothed[0], 3); // (5*0 + 0 + 9 + 9 + 9) / 9
+
+    let replicated = Kernel::smooth().with_edge(EdgeMode::Replicate).apply(&pixels, 3, 3, 255);
+    assert_eq!(replicated[1], 5); // (0 + 9 + 0 + 0 + 9 + 0 + 9 + 9 + 9) / 9
+}
+
+#[test]
+fn parse_kernel_text() {
+    let text = "# emboss\ndivisor 2\nbias 128\nedge wrap\n-2 -1 0\n-1 1 1\n 0 1 2\n";
+    let kernel: Kernel = text.parse().unwrap();
+    assert_eq!((kernel.width, kernel.height), (3, 3));
+    assert_eq!(kernel.divisor, 2.0);
+    assert_eq!(kernel.bias, 128.0);
+    assert_eq!(kernel.edge, EdgeMode::Wrap);
+    assert_eq!(kernel.weights[0], -2.0);
+
+    let kernel: Kernel = "edge center\n1 1 1".parse().unwrap();
+    assert_eq!(kernel.edge, EdgeMode::Center);
+}
+
+#[test]
+fn parse_bad_kernels() {
+    assert!("1 2 3\n4 5\n6 7 8".parse::<Kernel>().is_err());
+    assert!("1 2\n3 4".parse::<Kernel>().is_err());
+    assert!("1 x 3".parse::<Kernel>().is_err());
+    assert!("1 nan 3".parse::<Kernel>().is_err());
+    assert!("1 inf 3".parse::<Kernel>().is_err());
+    assert!("divisor inf\n1".parse::<Kernel>().is_err());
+    assert!("bias NaN\n1".parse::<Kernel>().is_err());
+    assert!("edge sideways\n1".parse::<Kernel>().is_err());
+    assert!("divisor 0\n1".parse::<Kernel>().is_err());
+    assert!("".parse::<Kernel>().is_err());
+}
@@ -0,0 +1,102 @@
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// Finds the exact euclidean distance from every foreground pixel to the nearest background
+/// pixel (background pixels are 0 away).
+///
+/// This is the algorithm of Felzenszwalb and Huttenlocher, which takes linear time: the squared
+/// distance transform separates into a 1D transform down every column and then along every row.
+/// Pixels outside the image don't count as background, so an image without any background is
+/// infinitely far from it everywhere.
+pub fn distances(foreground: &[bool], width: usize, height: usize) -> Vec<f64> {
+    let mut squared: Vec<f64> = foreground.iter()
+        .map(|&f| if f { f64::INFINITY } else { 0.0 })
+        .collect();
+
+    for x in 0..width {
+        let column: Vec<f64> = (0..height).map(|y| squared[y * width + x]).collect();
+        for (y, d) in transform(&column).into_iter().enumerate() {
+            squared[y * width + x] = d;
+        }
+    }
+    for row in squared.chunks_mut(width.max(1)) {
+        let transformed = transform(row);
+        row.copy_from_slice(&transformed);
+    }
+
+    squared.iter().map(|d| d.sqrt()).collect()
+}
+
+/// The 1D squared distance transform: `min(f[p] + (q - p)^2)` over every `p`, for every `q`.
+///
+/// The parabolas rooted at each finite `f[p]` are added to their lower envelope from left to
+/// right, dropping any that the new one hides, then the envelope is read off at every `q`.
+fn transform(f: &[f64]) -> Vec<f64> {
+    // roots of the parabolas in the envelope, and where each one takes over from the last
+    let mut roots: Vec<usize> = Vec::with_capacity(f.len());
+    let mut starts: Vec<f64> = Vec::with_capacity(f.len());
+
+    for q in (0..f.len()).filter(|&q| f[q].is_finite()) {
+        let mut start = f64::NEG_INFINITY;
+        while let Some(&p) = roots.last() {
+            let (qf, pf) = (q as f64, p as f64);
+            start = ((f[q] + qf * qf) - (f[p] + pf * pf)) / (2.0 * (qf - pf));
+            if start > *starts.last().unwrap() {
+                break;
+            }
+            roots.pop();
+            starts.pop();
+            start = f64::NEG_INFINITY;
+        }
+        roots.push(q);
+        starts.push(start);
+    }
+
+    if roots.is_empty() {
+        return vec![f64::INFINITY; f.len()];
+    }
+    let mut k = 0;
+    (0..f.len())
+        .map(|q| {
+            while k + 1 < roots.len() && starts[k + 1] < q as f64 {
+                k += 1;
+            }
+            let d = q as f64 - roots[k] as f64;
+            d * d + f[roots[k]]
+        })
+        .collect()
+}
+
+/// Scales distances so that the farthest pixel gets `maxval`, rounding to the nearest value.
+///
+/// If every distance is 0 the result is all 0, and if they're infinite it's all `maxval`.
+pub fn scale(distances: &[f64], maxval: usize) -> Vec<PxVal> {
+    let farthest = distances.iter().cloned().fold(0.0, f64::max);
+    distances.iter()
+        .map(|&d| {
+            if farthest == 0.0 {
+                0
+            } else if farthest.is_infinite() {
+                maxval as PxVal
+            } else {
+                (d / farthest * maxval as f64).round() as PxVal
+            }
+        })
+        .collect()
+}
+
+/// Encodes a plane of values as a grayscale PFM (portable float map) file.
+///
+/// PFM stores 32-bit floats whose byte order is given by the sign of the scale in the header
+/// (negative for little-endian), with rows running from the bottom of the image to the top.
+pub fn encode_pfm(plane: &[f64], width: usize, height: usize) -> Vec<u8> {
+    let mut data = format!("Pf\n{} {}\n-1.0\n", width, height).into_bytes();
+    for row in plane.chunks(width.max(1)).rev() {
+        for &v in row {
+            data.extend((v as f32).to_le_bytes());
+        }
+    }
+    data
+}
@@ -0,0 +1,90 @@
+////////////////////////////////
+// unit tests for distance.rs //
+////////////////////////////////
+use super::*;
+use crate::image::{GrayImage, Image, ImageManip, ImageType};
+
+/// Measures the distance to every background pixel, keeping the nearest
+fn brute_force(foreground: &[bool], width: usize) -> Vec<f64> {
+    (0..foreground.len())
+        .map(|i| {
+            let (x, y) = ((i % width) as f64, (i / width) as f64);
+            (0..foreground.len())
+                .filter(|&j| !foreground[j])
+                .map(|j| ((x - (j % width) as f64).powi(2) + (y - (j / width) as f64).powi(2)).sqrt())
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect()
+}
+
+/// Deterministic pseudo-random mask, mostly foreground
+fn noise(len: usize) -> Vec<bool> {
+    let mut state: u32 = 99;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            !(state >> 8).is_multiple_of(10)
+        })
+        .collect()
+}
+
+#[test]
+fn one_dimensional() {
+    let inf = f64::INFINITY;
+    assert_eq!(transform(&[inf, 0.0, inf, inf, inf, 0.0]), vec![1.0, 0.0, 1.0, 4.0, 1.0, 0.0]);
+    // finite values act as parabolas raised off the axis
+    assert_eq!(transform(&[5.0, inf, 0.0, inf]), vec![4.0, 1.0, 0.0, 1.0]);
+    assert!(transform(&[inf; 3]).iter().all(|d| d.is_infinite()));
+}
+
+#[test]
+fn matches_brute_force() {
+    for &(width, height) in &[(17, 11), (1, 9), (9, 1)] {
+        let fg = noise(width * height);
+        let exact = brute_force(&fg, width);
+        for (got, want) in distances(&fg, width, height).iter().zip(&exact) {
+            assert!(got == want || (got - want).abs() < 1e-9, "{} vs {}", got, want);
+        }
+    }
+}
+
+#[test]
+fn no_background() {
+    assert!(distances(&[true; 6], 3, 2).iter().all(|d| d.is_infinite()));
+    assert_eq!(scale(&distances(&[true; 6], 3, 2), 9), vec![9; 6]);
+    assert_eq!(scale(&distances(&[false; 6], 3, 2), 9), vec![0; 6]);
+}
+
+#[test]
+fn scaled_distance_map() {
+    let img = GrayImage {
+        width: 5,
+        height: 1,
+        maxval: 1,
+        pixels: vec![1, 0, 0, 0, 0],
+    };
+    // the farthest pixel is 4 away
+    assert_eq!(img.distance(100).pixels, vec![0, 25, 50, 75, 100]);
+    assert_eq!(img.distance(100).maxval, 100);
+}
+
+#[test]
+fn pfm() {
+    let data = encode_pfm(&[1.0, 2.0, 0.5, 0.0], 2, 2);
+    let header = b"Pf\n2 2\n-1.0\n";
+    assert_eq!(&data[..header.len()], header);
+
+    // the bottom row comes first
+    let values: Vec<f32> = data[header.len()..]
+        .chunks(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    assert_eq!(values, vec![0.5, 0.0, 1.0, 2.0]);
+
+    // the (white) background pixel is 0 away and the (black) foreground pixel 1, as
+    // little-endian floats
+    let img = Image(ImageType::Grayscale(GrayImage { width: 2, height: 1, maxval: 1, pixels: vec![1, 0] }));
+    let data = img.encode_distances();
+    assert_eq!(&data[..12], b"Pf\n2 1\n-1.0\n");
+    assert_eq!(data[12..], [0, 0, 0, 0, 0, 0, 0x80, 0x3f]);
+}
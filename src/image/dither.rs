@@ -0,0 +1,144 @@
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// An error diffusion kernel, which spreads each pixel's quantization error onto the neighbors
+/// which haven't been quantized yet.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Diffusion {
+    FloydSteinberg,
+    /// Bill Atkinson's kernel, which only spreads 3/4 of the error, for more contrast
+    Atkinson,
+    /// The kernel of Jarvis, Judice and Ninke, which spreads error further than Floyd-Steinberg
+    JarvisJudiceNinke,
+}
+
+/// How to reduce an image to fewer values.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum DitherMethod {
+    /// Error diffusion, optionally scanning alternate rows right to left ("serpentine"), which
+    /// avoids the diagonal artifacts of always scanning the same way
+    Diffuse { kernel: Diffusion, serpentine: bool },
+    /// Ordered dithering with a Bayer matrix of this size (2, 4 or 8)
+    Bayer(usize),
+}
+
+/// Parameters of a dither to a new `maxval`.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Dither {
+    pub method: DitherMethod,
+    pub maxval: usize,
+}
+
+impl Diffusion {
+    /// The kernel's `(dx, dy, weight)` taps (for scanning left to right), and their divisor
+    fn taps(self) -> (&'static [(isize, usize, f64)], f64) {
+        match self {
+            Diffusion::FloydSteinberg => (&[
+                (1, 0, 7.0),
+                (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0),
+            ], 16.0),
+            Diffusion::Atkinson => (&[
+                (1, 0, 1.0), (2, 0, 1.0),
+                (-1, 1, 1.0), (0, 1, 1.0), (1, 1, 1.0),
+                (0, 2, 1.0),
+            ], 8.0),
+            Diffusion::JarvisJudiceNinke => (&[
+                (1, 0, 7.0), (2, 0, 5.0),
+                (-2, 1, 3.0), (-1, 1, 5.0), (0, 1, 7.0), (1, 1, 5.0), (2, 1, 3.0),
+                (-2, 2, 1.0), (-1, 2, 3.0), (0, 2, 5.0), (1, 2, 3.0), (2, 2, 1.0),
+            ], 48.0),
+        }
+    }
+
+    /// Quantizes `planes` of values in place, one pixel at a time, diffusing the error.
+    ///
+    /// `quantize` replaces one pixel's values (one from each plane) with their quantized values,
+    /// so colors can be quantized together rather than channel by channel. Errors which would
+    /// land beyond the edge of the image are dropped.
+    pub fn diffuse<F>(self, planes: &mut [Vec<f64>], width: usize, height: usize, serpentine: bool, mut quantize: F)
+    where
+        F: FnMut(&mut [f64])
+    {
+        let (taps, divisor) = self.taps();
+        let mut values = vec![0.0; planes.len()];
+        for y in 0..height {
+            let reverse = serpentine && y % 2 == 1;
+            for i in 0..width {
+                let x = if reverse { width - 1 - i } else { i };
+                let idx = y * width + x;
+                for (v, plane) in values.iter_mut().zip(planes.iter()) {
+                    *v = plane[idx];
+                }
+                quantize(&mut values);
+
+                for (v, plane) in values.iter().zip(planes.iter_mut()) {
+                    let error = plane[idx] - v;
+                    plane[idx] = *v;
+                    for &(dx, dy, weight) in taps {
+                        let nx = if reverse { x as isize - dx } else { x as isize + dx };
+                        if nx >= 0 && (nx as usize) < width && y + dy < height {
+                            plane[(y + dy) * width + nx as usize] += error * weight / divisor;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Builds an `n`x`n` Bayer threshold matrix (for `n` a power of two), holding each of the values
+/// `0..n*n` once, in row-major order.
+pub fn bayer_matrix(n: usize) -> Vec<usize> {
+    let mut matrix = vec![0];
+    let mut size = 1;
+    while size < n {
+        let mut next = vec![0; 4 * size * size];
+        for y in 0..size {
+            for x in 0..size {
+                let m = 4 * matrix[y * size + x];
+                next[y * 2 * size + x] = m;
+                next[y * 2 * size + x + size] = m + 2;
+                next[(y + size) * 2 * size + x] = m + 3;
+                next[(y + size) * 2 * size + x + size] = m + 1;
+            }
+        }
+        matrix = next;
+        size *= 2;
+    }
+    matrix
+}
+
+impl Dither {
+    /// Reduces a plane of pixels from `0..=maxval` to `0..=self.maxval`.
+    pub fn apply(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        let target = self.maxval as f64;
+        let scale = target / maxval as f64;
+        match self.method {
+            DitherMethod::Diffuse { kernel, serpentine } => {
+                let mut planes = [pixels.iter().map(|&v| v as f64 * scale).collect::<Vec<f64>>()];
+                kernel.diffuse(&mut planes, width, height, serpentine, |v| {
+                    v[0] = v[0].round().clamp(0.0, target);
+                });
+                let [plane] = planes;
+                plane.into_iter().map(|v| v as PxVal).collect()
+            }
+            DitherMethod::Bayer(n) => {
+                let matrix = bayer_matrix(n);
+                let cells = (n * n) as f64;
+                pixels.iter()
+                    .enumerate()
+                    .map(|(i, &v)| {
+                        let (x, y) = (i % width, i / width);
+                        let offset = (matrix[(y % n) * n + x % n] as f64 + 0.5) / cells;
+                        (v as f64 * scale + offset).floor().clamp(0.0, target) as PxVal
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//////////////////////////////
+// unit tests for dither.rs //
+//////////////////////////////
+use super::*;
+use crate::image::{ColorImage, GrayImage, ImageManip};
+
+const KERNELS: [Diffusion; 3] = [Diffusion::FloydSteinberg, Diffusion::Atkinson, Diffusion::JarvisJudiceNinke];
+
+fn mean(pixels: &[PxVal]) -> f64 {
+    pixels.iter().map(|&v| v as f64).sum::<f64>() / pixels.len() as f64
+}
+
+#[test]
+fn bayer_matrices() {
+    assert_eq!(bayer_matrix(2), vec![0, 2, 3, 1]);
+    assert_eq!(bayer_matrix(4), vec![
+         0,  8,  2, 10,
+        12,  4, 14,  6,
+         3, 11,  1,  9,
+        15,  7, 13,  5,
+    ]);
+    let mut eight = bayer_matrix(8);
+    eight.sort_unstable();
+    assert_eq!(eight, (0..64).collect::<Vec<usize>>());
+}
+
+#[test]
+fn bayer_mid_gray_is_a_checkerboard() {
+    let dither = Dither { method: DitherMethod::Bayer(2), maxval: 1 };
+    assert_eq!(dither.apply(&[2; 16], 4, 4, 4), vec![
+        0, 1, 0, 1,
+        1, 0, 1, 0,
+        0, 1, 0, 1,
+        1, 0, 1, 0,
+    ]);
+    // a quarter of the way up lights one cell in four
+    let out = dither.apply(&[1; 16], 4, 4, 4);
+    assert_eq!(out.iter().filter(|&&v| v == 1).count(), 4);
+}
+
+#[test]
+fn exact_levels_are_kept() {
+    // 0, 85, 170 and 255 are exactly the levels of a maxval of 3
+    let pixels: Vec<PxVal> = (0..64).map(|i| 85 * (i % 4)).collect();
+    for &kernel in &KERNELS {
+        let dither = Dither { method: DitherMethod::Diffuse { kernel, serpentine: false }, maxval: 3 };
+        assert_eq!(dither.apply(&pixels, 8, 8, 255), (0..64).map(|i| i % 4).collect::<Vec<PxVal>>());
+    }
+    for &n in &[2, 4, 8] {
+        let dither = Dither { method: DitherMethod::Bayer(n), maxval: 3 };
+        assert_eq!(dither.apply(&pixels, 8, 8, 255), (0..64).map(|i| i % 4).collect::<Vec<PxVal>>());
+    }
+}
+
+#[test]
+fn diffusion_preserves_average() {
+    let pixels = vec![100; 32 * 32];
+    for &kernel in &KERNELS {
+        for &serpentine in &[false, true] {
+            let dither = Dither { method: DitherMethod::Diffuse { kernel, serpentine }, maxval: 1 };
+            let out = dither.apply(&pixels, 32, 32, 255);
+            assert!(out.iter().all(|&v| v <= 1));
+            // Atkinson drops a quarter of the error, so it's allowed to drift further
+            let tolerance = if kernel == Diffusion::Atkinson { 0.05 } else { 0.02 };
+            assert!((mean(&out) - 100.0 / 255.0).abs() < tolerance, "{:?} {}", kernel, mean(&out));
+        }
+    }
+}
+
+#[test]
+fn floyd_steinberg_by_hand() {
+    // 0.5 rounds up to 1 leaving -0.5, which pulls the next pixel (0.5 - 7/32) down to 0
+    let dither = Dither {
+        method: DitherMethod::Diffuse { kernel: Diffusion::FloydSteinberg, serpentine: false },
+        maxval: 1,
+    };
+    assert_eq!(dither.apply(&[1, 1, 1, 1], 4, 1, 2), vec![1, 0, 1, 0]);
+}
+
+#[test]
+fn serpentine_reverses_alternate_rows() {
+    let plain = Dither {
+        method: DitherMethod::Diffuse { kernel: Diffusion::FloydSteinberg, serpentine: false },
+        maxval: 1,
+    };
+    let serpentine = Dither {
+        method: DitherMethod::Diffuse { kernel: Diffusion::FloydSteinberg, serpentine: true },
+        maxval: 1,
+    };
+    // scanning the second row from the right moves where its error adds up to a dot
+    let pixels = vec![2; 12];
+    assert_eq!(plain.apply(&pixels, 4, 3, 10), vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(serpentine.apply(&pixels, 4, 3, 10), vec![0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn dithered_images_take_new_maxval() {
+    let dither = Dither { method: DitherMethod::Bayer(4), maxval: 15 };
+    let gray = GrayImage { width: 2, height: 1, maxval: 255, pixels: vec![0, 255] };
+    let out = gray.dither(&dither);
+    assert_eq!((out.maxval, out.pixels), (15, vec![0, 15]));
+
+    let color = ColorImage {
+        width: 2,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![255, 0],
+        gpixels: vec![0, 255],
+        bpixels: vec![0, 0],
+    };
+    let out = color.dither(&dither);
+    assert_eq!((out.maxval, out.rpixels, out.gpixels, out.bpixels), (15, vec![15, 0], vec![0, 15], vec![0, 0]));
+}
@@ -0,0 +1,106 @@
+use super::blur::gaussian_plane;
+use super::convolve::Kernel;
+use super::{plane_from_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// The pair of 3x3 kernels used to estimate a gradient.
+///
+/// Each operator differentiates along one axis and smooths along the other, so its kernels are
+/// separable. They differ only in how heavily the smoothing favors the center.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Operator {
+    Sobel,
+    Prewitt,
+    Scharr,
+}
+
+/// Which property of the gradient to output
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum GradientOutput {
+    Magnitude,
+    Direction,
+}
+
+/// An edge detector, producing a grayscale image from an image's luminance.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum EdgeDetector {
+    Gradient(Operator, GradientOutput),
+    /// The laplacian of a gaussian with the given `sigma` (0 for the plain laplacian)
+    Laplacian(f64),
+}
+
+impl Operator {
+    /// The smoothing applied perpendicular to the derivative
+    fn smoothing(self) -> Vec<f64> {
+        match self {
+            Operator::Sobel => vec![1.0, 2.0, 1.0],
+            Operator::Prewitt => vec![1.0, 1.0, 1.0],
+            Operator::Scharr => vec![3.0, 10.0, 3.0],
+        }
+    }
+
+    /// Estimates the horizontal and vertical gradients of a plane.
+    ///
+    /// Positive values mean the plane gets brighter towards the right or the bottom.
+    pub fn gradient(self, plane: &[f64], width: usize, height: usize) -> (Vec<f64>, Vec<f64>) {
+        let derivative = vec![-1.0, 0.0, 1.0];
+        let gx = Kernel::separable(derivative.clone(), self.smoothing()).unwrap();
+        let gy = Kernel::separable(self.smoothing(), derivative).unwrap();
+        (gx.convolve(plane, width, height), gy.convolve(plane, width, height))
+    }
+}
+
+impl EdgeDetector {
+    /// Detects edges in a plane, scaling the result to `0..=maxval`.
+    ///
+    /// - gradient magnitudes are scaled so the strongest edge becomes `maxval`
+    /// - gradient directions map the angle from `-pi` to `pi` onto `0..=maxval`, clockwise from
+    ///   pointing right
+    /// - laplacians are signed, so zero becomes `maxval / 2` and the strongest response becomes
+    ///   either `0` or `maxval`
+    pub fn apply(&self, plane: &[f64], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        let max = maxval as f64;
+        let out: Vec<f64> = match *self {
+            EdgeDetector::Gradient(op, output) => {
+                let (gx, gy) = op.gradient(plane, width, height);
+                match output {
+                    GradientOutput::Magnitude => {
+                        let mags: Vec<f64> = gx.iter().zip(gy.iter()).map(|(x, y)| x.hypot(*y)).collect();
+                        let scale = max / largest(&mags);
+                        mags.iter().map(|m| m * scale).collect()
+                    }
+                    GradientOutput::Direction => gx.iter()
+                        .zip(gy.iter())
+                        .map(|(x, y)| (y.atan2(*x) + std::f64::consts::PI) / (2.0 * std::f64::consts::PI) * max)
+                        .collect(),
+                }
+            }
+            EdgeDetector::Laplacian(sigma) => {
+                let lap = laplacian(&gaussian_plane(plane, width, height, sigma, false), width, height);
+                let scale = max / 2.0 / largest(&lap);
+                lap.iter().map(|l| max / 2.0 + l * scale).collect()
+            }
+        };
+        plane_from_f64(&out, maxval)
+    }
+}
+
+/// Applies the 4-neighbor laplacian `N + S + E + W - 4*v` to a plane.
+pub fn laplacian(plane: &[f64], width: usize, height: usize) -> Vec<f64> {
+    Kernel::new(3, 3, vec![
+        0.0,  1.0, 0.0,
+        1.0, -4.0, 1.0,
+        0.0,  1.0, 0.0,
+    ]).unwrap().convolve(plane, width, height)
+}
+
+/// The largest magnitude in a plane, or 1 if it's entirely zero (to avoid dividing by zero)
+fn largest(plane: &[f64]) -> f64 {
+    let m = plane.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+    if m == 0.0 { 1.0 } else { m }
+}
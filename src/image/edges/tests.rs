@@ -0,0 +1,76 @@
+/////////////////////////////
+// unit tests for edges.rs //
+/////////////////////////////
+use super::*;
+
+/// A 6x6 plane which is 0 on the left half and 100 on the right half
+fn vertical_edge() -> Vec<f64> {
+    (0..36).map(|i| if i % 6 < 3 { 0.0 } else { 100.0 }).collect()
+}
+
+#[test]
+fn gradients_point_across_edge() {
+    for &op in &[Operator::Sobel, Operator::Prewitt, Operator::Scharr] {
+        let (gx, gy) = op.gradient(&vertical_edge(), 6, 6);
+        assert!(gy.iter().all(|&g| g == 0.0));
+        assert!(gx[2] > 0.0 && gx[3] > 0.0);
+        assert_eq!(gx[0], 0.0);
+        assert_eq!(gx[5], 0.0);
+    }
+}
+
+#[test]
+fn sobel_weights() {
+    // across the edge, each row of the kernel sees a jump of 100: (1 + 2 + 1) * 100
+    let (gx, _) = Operator::Sobel.gradient(&vertical_edge(), 6, 6);
+    assert_eq!(gx[6 * 2 + 2], 400.0);
+    let (gx, _) = Operator::Scharr.gradient(&vertical_edge(), 6, 6);
+    assert_eq!(gx[6 * 2 + 2], 1600.0);
+}
+
+#[test]
+fn magnitude_normalized_to_maxval() {
+    let detector = EdgeDetector::Gradient(Operator::Sobel, GradientOutput::Magnitude);
+    let out = detector.apply(&vertical_edge(), 6, 6, 255);
+    assert_eq!(out[2], 255);
+    assert_eq!(out[3], 255);
+    assert_eq!(out[0], 0);
+    assert_eq!(*out.iter().max().unwrap(), 255);
+}
+
+#[test]
+fn direction_of_rightward_gradient() {
+    // pointing right is an angle of 0, halfway between -pi and pi
+    let detector = EdgeDetector::Gradient(Operator::Prewitt, GradientOutput::Direction);
+    let out = detector.apply(&vertical_edge(), 6, 6, 254);
+    assert_eq!(out[2], 127);
+
+    // and pointing down is a quarter turn clockwise
+    let transposed: Vec<f64> = (0..36).map(|i| vertical_edge()[(i % 6) * 6 + i / 6]).collect();
+    let out = detector.apply(&transposed, 6, 6, 256);
+    assert_eq!(out[12], 192);
+}
+
+#[test]
+fn flat_plane_has_no_edges() {
+    let flat = vec![42.0; 25];
+    let magnitude = EdgeDetector::Gradient(Operator::Sobel, GradientOutput::Magnitude);
+    assert_eq!(magnitude.apply(&flat, 5, 5, 255), vec![0; 25]);
+    assert_eq!(EdgeDetector::Laplacian(0.0).apply(&flat, 5, 5, 255), vec![128; 25]);
+}
+
+#[test]
+fn laplacian_is_signed_around_midpoint() {
+    let out = EdgeDetector::Laplacian(0.0).apply(&vertical_edge(), 6, 6, 255);
+    assert_eq!(out[0], 128);
+    assert_eq!(out[2], 255); // dark side of the edge, below its neighbors' average
+    assert_eq!(out[3], 0);
+}
+
+#[test]
+fn log_spreads_response() {
+    let plain = EdgeDetector::Laplacian(0.0).apply(&vertical_edge(), 6, 6, 255);
+    let log = EdgeDetector::Laplacian(1.0).apply(&vertical_edge(), 6, 6, 255);
+    assert_eq!(plain[1], 128);
+    assert!(log[1] > 128);
+}
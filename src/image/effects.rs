@@ -0,0 +1,68 @@
+use super::channels::ChannelMixer;
+use super::resize::downscale;
+use super::{plane_from_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// The classic sepia toning matrix, which warms and slightly desaturates a color image
+pub const SEPIA: ChannelMixer = ChannelMixer([
+    [0.393, 0.769, 0.189],
+    [0.349, 0.686, 0.168],
+    [0.272, 0.534, 0.131],
+]);
+
+/// Builds a table snapping every value from 0 to `maxval` to the nearest of `levels` evenly
+/// spaced levels (which include 0 and `maxval`).
+pub fn posterize_lut(levels: usize, maxval: usize) -> Vec<PxVal> {
+    let steps = (levels.max(2) - 1) as f64;
+    let max = maxval as f64;
+    (0..=maxval)
+        .map(|v| ((v as f64 * steps / max).round() * max / steps).round() as PxVal)
+        .collect()
+}
+
+/// Builds a table inverting every value above `threshold * maxval`, and keeping the rest.
+pub fn solarize_lut(threshold: f64, maxval: usize) -> Vec<PxVal> {
+    let t = threshold * maxval as f64;
+    (0..=maxval)
+        .map(|v| if v as f64 > t { maxval - v } else { v } as PxVal)
+        .collect()
+}
+
+/// Maps the luminance of an image onto the range of colors between two.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Duotone {
+    /// The color of black, as red, green and blue fractions from 0 to 1
+    pub dark: [f64; 3],
+    /// The color of white, as red, green and blue fractions from 0 to 1
+    pub light: [f64; 3],
+}
+
+impl Duotone {
+    /// Colors a plane of luminance values from 0 to `maxval`, returning red, green and blue planes.
+    pub fn apply(&self, luma: &[f64], maxval: usize) -> [Vec<PxVal>; 3] {
+        let max = maxval as f64;
+        [0, 1, 2].map(|c| {
+            let (dark, light) = (self.dark[c] * max, self.light[c] * max);
+            let plane: Vec<f64> = luma.iter().map(|y| dark + y / max * (light - dark)).collect();
+            plane_from_f64(&plane, maxval)
+        })
+    }
+}
+
+/// Replaces every `block`x`block` square of a plane with its average, keeping the plane's size.
+///
+/// Blocks are aligned to the top left corner, so those along the right and bottom edges may be
+/// partial (as for [`downscale`]).
+pub fn pixelate(pixels: &[PxVal], width: usize, height: usize, block: usize) -> Vec<PxVal> {
+    let block = block.max(1);
+    let (averages, blocks_wide, _) = downscale(pixels, width, height, block);
+    (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+            averages[(y / block) * blocks_wide + x / block]
+        })
+        .collect()
+}
@@ -0,0 +1,83 @@
+///////////////////////////////
+// unit tests for effects.rs //
+///////////////////////////////
+use super::*;
+use crate::image::{ColorImage, GrayImage, ImageManip};
+
+fn gray(width: usize, height: usize, pixels: Vec<PxVal>) -> GrayImage {
+    GrayImage { width, height, maxval: 255, pixels }
+}
+
+#[test]
+fn posterize_levels() {
+    let lut = posterize_lut(2, 255);
+    assert_eq!((lut[0], lut[127], lut[128], lut[255]), (0, 0, 255, 255));
+
+    let lut = posterize_lut(4, 255);
+    let mut levels = lut.clone();
+    levels.dedup();
+    assert_eq!(levels, vec![0, 85, 170, 255]);
+    assert_eq!((lut[42], lut[43], lut[200]), (0, 85, 170));
+
+    let out = gray(3, 1, vec![10, 100, 250]).posterize(3);
+    assert_eq!(out.pixels, vec![0, 128, 255]);
+}
+
+#[test]
+fn solarize_inverts_highlights() {
+    let lut = solarize_lut(0.5, 255);
+    assert_eq!((lut[0], lut[127], lut[128], lut[255]), (0, 127, 127, 0));
+    assert_eq!(solarize_lut(1.0, 15), (0..=15).collect::<Vec<PxVal>>());
+    assert_eq!(solarize_lut(0.0, 3), vec![0, 2, 1, 0]);
+
+    let out = gray(2, 1, vec![50, 200]).solarize(0.5);
+    assert_eq!(out.pixels, vec![50, 55]);
+}
+
+#[test]
+fn sepia_is_warm() {
+    let out = gray(2, 1, vec![100, 255]).mix(&SEPIA);
+    assert_eq!(out.rpixels, vec![135, 255]);
+    assert_eq!(out.gpixels, vec![120, 255]);
+    assert_eq!(out.bpixels, vec![94, 239]);
+}
+
+#[test]
+fn duotone_between_colors() {
+    let duotone = Duotone { dark: [0.0, 0.0, 0.5], light: [1.0, 1.0, 0.5] };
+    let out = gray(3, 1, vec![0, 51, 255]).duotone(&duotone);
+    assert_eq!(out.rpixels, vec![0, 51, 255]);
+    assert_eq!(out.gpixels, vec![0, 51, 255]);
+    assert_eq!(out.bpixels, vec![128, 128, 128]);
+
+    // color images are mapped by their luminance
+    let color = ColorImage {
+        width: 1,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![0],
+        gpixels: vec![255],
+        bpixels: vec![0],
+    };
+    let out = color.duotone(&duotone);
+    assert_eq!((out.rpixels, out.bpixels), (vec![153], vec![128]));
+}
+
+#[test]
+fn pixelate_blocks() {
+    let pixels = vec![
+        0, 10, 20, 30, 40,
+        50, 60, 70, 80, 90,
+        100, 110, 120, 130, 140,
+    ];
+    assert_eq!(pixelate(&pixels, 5, 3, 2), vec![
+        30, 30, 50, 50, 65,
+        30, 30, 50, 50, 65,
+        105, 105, 125, 125, 140,
+    ]);
+    assert_eq!(pixelate(&pixels, 5, 3, 1), pixels);
+
+    let out = gray(5, 3, pixels.clone()).pixelate(5);
+    assert_eq!((out.width, out.height), (5, 3));
+    assert!(out.pixels.iter().all(|&v| v == 70));
+}
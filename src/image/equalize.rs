@@ -0,0 +1,169 @@
+use super::histogram::counts;
+use super::{apply_lut, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// A histogram equalization method.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Equalize {
+    /// Remap values so that the histogram of the whole image is as flat as possible
+    Global,
+    /// Contrast-limited adaptive histogram equalization
+    Adaptive(Clahe),
+}
+
+/// Parameters of contrast-limited adaptive histogram equalization (CLAHE).
+///
+/// The image is divided into a grid of tiles which are each equalized separately, with each
+/// tile's histogram clipped so that no bin holds more than `clip_limit` times the average (which
+/// limits how much noise in flat regions gets amplified). Each pixel's new value is blended
+/// bilinearly from the mappings of the four nearest tiles, so tile boundaries don't show.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Clahe {
+    pub tiles_x: usize,
+    pub tiles_y: usize,
+    pub clip_limit: f64,
+}
+
+impl Default for Clahe {
+    fn default() -> Clahe {
+        Clahe { tiles_x: 8, tiles_y: 8, clip_limit: 2.0 }
+    }
+}
+
+impl Equalize {
+    pub fn apply(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        match self {
+            Equalize::Global => {
+                apply_lut(pixels, &mapping(&counts(pixels, maxval), maxval))
+            }
+            Equalize::Adaptive(clahe) => clahe.apply(pixels, width, height, maxval),
+        }
+    }
+}
+
+/// Builds the lookup table which equalizes a histogram.
+///
+/// Each value maps to its position in the cumulative distribution, stretched so that the darkest
+/// value present becomes 0 and the lightest becomes `maxval`.
+pub fn mapping(hist: &[usize], maxval: usize) -> Vec<PxVal> {
+    let total: usize = hist.iter().sum();
+    let first = hist.iter().find(|&&c| c > 0).cloned().unwrap_or(0);
+    if total == first {
+        // a single value (or nothing at all) can't be spread out
+        return (0..=maxval).map(|v| v as PxVal).collect();
+    }
+
+    let mut cdf = 0;
+    hist.iter()
+        .map(|&c| {
+            cdf += c;
+            let v = cdf.saturating_sub(first) as f64 / (total - first) as f64 * maxval as f64;
+            v.round() as PxVal
+        })
+        .collect()
+}
+
+/// The most bins a CLAHE tile's histogram is counted in.
+///
+/// Deeper images are binned down to this, since with one bin per value a 16-bit tile would have
+/// far fewer pixels than bins, and every occupied bin would be over any sensible clip limit.
+const CLAHE_BINS: usize = 256;
+
+/// The lowest value which falls into `bin`, when `0..=maxval` is split into `bins` bins
+fn bin_start(bin: usize, bins: usize, maxval: usize) -> usize {
+    (bin * (maxval + 1)).div_ceil(bins)
+}
+
+impl Clahe {
+    pub fn apply(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        let tiles_x = self.tiles_x.clamp(1, width.max(1));
+        let tiles_y = self.tiles_y.clamp(1, height.max(1));
+        let bins = (maxval + 1).min(CLAHE_BINS);
+        let bin = |v: PxVal| v as usize * bins / (maxval + 1);
+
+        // tile boundaries, so that every pixel falls in exactly one tile
+        let bounds = |tiles: usize, len: usize, t: usize| (t * len / tiles, (t + 1) * len / tiles);
+
+        let mut luts = Vec::with_capacity(tiles_x * tiles_y);
+        for ty in 0..tiles_y {
+            let (y0, y1) = bounds(tiles_y, height, ty);
+            for tx in 0..tiles_x {
+                let (x0, x1) = bounds(tiles_x, width, tx);
+                let mut hist = vec![0usize; bins];
+                for y in y0..y1 {
+                    for &v in &pixels[y * width + x0..y * width + x1] {
+                        hist[bin(v)] += 1;
+                    }
+                }
+                luts.push(self.tile_mapping(&hist, maxval));
+            }
+        }
+
+        // position of `i` relative to the tile centers, as (lower tile, upper tile, weight of upper)
+        let locate = |tiles: usize, len: usize, i: usize| -> (usize, usize, f64) {
+            let pos = (i as f64 + 0.5) * tiles as f64 / len as f64 - 0.5;
+            if pos <= 0.0 {
+                (0, 0, 0.0)
+            } else if pos >= (tiles - 1) as f64 {
+                (tiles - 1, tiles - 1, 0.0)
+            } else {
+                let lower = pos.floor() as usize;
+                (lower, lower + 1, pos - lower as f64)
+            }
+        };
+
+        let mut out = Vec::with_capacity(pixels.len());
+        for y in 0..height {
+            let (ty0, ty1, fy) = locate(tiles_y, height, y);
+            for x in 0..width {
+                let (tx0, tx1, fx) = locate(tiles_x, width, x);
+                let v = pixels[y * width + x];
+
+                // how far through its bin `v` is, to interpolate between the ends of the bin's mapping
+                let b = bin(v);
+                let (start, end) = (bin_start(b, bins, maxval), bin_start(b + 1, bins, maxval));
+                let through = (v as usize + 1 - start) as f64 / (end - start) as f64;
+                let at = |tx: usize, ty: usize| {
+                    let lut = &luts[ty * tiles_x + tx];
+                    let below = if b == 0 { 0.0 } else { lut[b - 1] };
+                    below + (lut[b] - below) * through
+                };
+
+                let top = at(tx0, ty0) * (1.0 - fx) + at(tx1, ty0) * fx;
+                let bottom = at(tx0, ty1) * (1.0 - fx) + at(tx1, ty1) * fx;
+                out.push((top * (1.0 - fy) + bottom * fy).round() as PxVal);
+            }
+        }
+        out
+    }
+
+    /// Clips a tile's histogram, redistributes the excess evenly, and builds its mapping.
+    ///
+    /// The mapping gives the (unrounded) new value for the top of each bin, which is where the
+    /// bin's cumulative distribution ends.
+    fn tile_mapping(&self, hist: &[usize], maxval: usize) -> Vec<f64> {
+        let bins = hist.len();
+        let total: usize = hist.iter().sum();
+        if total == 0 {
+            return (1..=bins).map(|b| (bin_start(b, bins, maxval) - 1) as f64).collect();
+        }
+
+        let limit = (self.clip_limit * total as f64 / bins as f64).max(1.0);
+        let excess: f64 = hist.iter().map(|&c| (c as f64 - limit).max(0.0)).sum();
+        let share = excess / bins as f64;
+
+        // clipped histograms have their excess spread over every bin, so the plain stretch from
+        // the darkest value (as in `mapping`) would undo the clipping
+        let mut cdf = 0.0;
+        hist.iter()
+            .map(|&c| {
+                cdf += (c as f64).min(limit) + share;
+                cdf / total as f64 * maxval as f64
+            })
+            .collect()
+    }
+}
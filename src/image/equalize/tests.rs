@@ -0,0 +1,133 @@
+////////////////////////////////
+// unit tests for equalize.rs //
+////////////////////////////////
+use super::*;
+
+#[test]
+fn global_spreads_values() {
+    // a dim, low-contrast image uses the whole range afterwards
+    let pixels = vec![10, 10, 11, 11, 12, 12, 13, 13];
+    let out = Equalize::Global.apply(&pixels, 4, 2, 255);
+    assert_eq!(out, vec![0, 0, 85, 85, 170, 170, 255, 255]);
+}
+
+#[test]
+fn global_ignores_outliers() {
+    // a plain min/max stretch would do nothing here, but equalization still separates the
+    // crowded middle values
+    let mut pixels = vec![100; 50];
+    pixels.extend(vec![110; 50]);
+    pixels[0] = 0;
+    pixels[99] = 255;
+    let out = Equalize::Global.apply(&pixels, 10, 10, 255);
+    assert_eq!(out[0], 0);
+    assert_eq!(out[99], 255);
+    assert!(out[1] < 130);
+    assert!(out[98] > 250);
+}
+
+#[test]
+fn global_is_monotonic() {
+    let pixels: Vec<PxVal> = (0..64).map(|i| (i * i % 97) as PxVal).collect();
+    let out = Equalize::Global.apply(&pixels, 8, 8, 255);
+    for i in 0..64 {
+        for j in 0..64 {
+            if pixels[i] < pixels[j] {
+                assert!(out[i] <= out[j]);
+            }
+        }
+    }
+}
+
+#[test]
+fn constant_image_unchanged() {
+    let pixels = vec![42; 16];
+    assert_eq!(Equalize::Global.apply(&pixels, 4, 4, 255), pixels);
+}
+
+#[test]
+fn clahe_limits_contrast() {
+    // a nearly flat image: unlimited equalization blows the tiny difference up to the full range
+    let pixels: Vec<PxVal> = (0..256).map(|i| if i % 2 == 0 { 100 } else { 101 }).collect();
+    let global = Equalize::Global.apply(&pixels, 16, 16, 255);
+    assert_eq!(global[1] - global[0], 255);
+
+    let clahe = Clahe { tiles_x: 2, tiles_y: 2, clip_limit: 2.0 };
+    let out = clahe.apply(&pixels, 16, 16, 255);
+    assert!(out[1] - out[0] < 10);
+}
+
+#[test]
+fn clahe_limits_contrast_of_deep_images() {
+    // the same nearly flat image at 16 bits is limited just as much
+    let pixels: Vec<PxVal> = (0..256).map(|i| if i % 2 == 0 { 100 * 257 } else { 101 * 257 }).collect();
+    let clahe = Clahe { tiles_x: 2, tiles_y: 2, clip_limit: 2.0 };
+    let out = clahe.apply(&pixels, 16, 16, 65535);
+    assert!(out[1] - out[0] < 10 * 257);
+
+    // and a ramp is mapped the same way as at 8 bits, when each value is at the top of its bin
+    let shallow: Vec<PxVal> = (0..256).collect();
+    let deep: Vec<PxVal> = shallow.iter().map(|&v| v * 256 + 255).collect();
+    let shallow = clahe.apply(&shallow, 16, 16, 255);
+    let deep = clahe.apply(&deep, 16, 16, 65535);
+    for (s, d) in shallow.iter().zip(&deep) {
+        assert!((*s as f64 * 257.0 - *d as f64).abs() <= 257.0, "{} vs {}", s, d);
+    }
+}
+
+#[test]
+fn clahe_adapts_to_regions() {
+    // left half dark with slight texture, right half bright with slight texture
+    let pixels: Vec<PxVal> = (0..256)
+        .map(|i| {
+            let base = if i % 16 < 8 { 20 } else { 220 };
+            base + (i % 3) as PxVal * 4
+        })
+        .collect();
+    let clahe = Clahe { tiles_x: 2, tiles_y: 1, clip_limit: 4.0 };
+    let out = clahe.apply(&pixels, 16, 16, 255);
+
+    // the texture in each half gets stretched more than a global equalization would manage
+    let spread = |p: &[PxVal], x0: usize| {
+        let v: Vec<PxVal> = (0..16).flat_map(|y| (x0..x0 + 3).map(move |x| y * 16 + x)).map(|i| p[i]).collect();
+        v.iter().max().unwrap() - v.iter().min().unwrap()
+    };
+    assert!(spread(&out, 0) > spread(&pixels, 0));
+    assert!(spread(&out, 13) > spread(&pixels, 13));
+}
+
+#[test]
+fn clahe_blends_smoothly() {
+    // a smooth horizontal ramp stays monotonic across tile boundaries
+    let pixels: Vec<PxVal> = (0..64 * 4).map(|i| (i % 64) as PxVal * 4).collect();
+    let clahe = Clahe { tiles_x: 4, tiles_y: 1, clip_limit: 3.0 };
+    let out = clahe.apply(&pixels, 64, 4, 255);
+    for x in 1..64 {
+        assert!(out[x] >= out[x - 1], "{:?}", &out[..64]);
+    }
+}
+
+#[test]
+fn color_equalizes_luminance() {
+    use crate::image::{ColorImage, ImageManip};
+
+    let img = ColorImage {
+        width: 2,
+        height: 2,
+        maxval: 255,
+        rpixels: vec![24, 36, 48, 60],
+        gpixels: vec![20, 30, 40, 50],
+        bpixels: vec![20, 30, 40, 50],
+    };
+    let out = img.equalize(&Equalize::Global);
+
+    // the luminance is spread across the whole range
+    assert_eq!(out.gpixels[0], 0);
+    assert_eq!(out.rpixels[3], 255);
+    assert!(out.gpixels[1] > 60 && out.gpixels[2] > 140);
+
+    // and pixels which don't clip keep their ratio of red to green
+    for i in 1..3 {
+        assert!((out.rpixels[i] as f64 / out.gpixels[i] as f64 - 1.2).abs() < 0.02);
+    }
+}
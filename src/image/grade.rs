@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests;
+
+/// A color grading adjustment, applied to each pixel in HSL.
+///
+/// Grays have no hue and no saturation, so none of these change them.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ColorGrade {
+    /// Rotate every hue by this many degrees
+    Hue(f64),
+    /// Multiply every saturation by this factor
+    Saturation(f64),
+    /// Raise (or, if negative, lower) saturation by this fraction of the way towards full,
+    /// weighted towards pixels which are only slightly saturated, from -1 to 1
+    Vibrance(f64),
+}
+
+impl ColorGrade {
+    /// Adjusts a single `[h, s, l]` color.
+    pub fn apply(self, [h, s, l]: [f64; 3]) -> [f64; 3] {
+        match self {
+            ColorGrade::Hue(degrees) => [(h + degrees).rem_euclid(360.0), s, l],
+            ColorGrade::Saturation(factor) => [h, (s * factor).clamp(0.0, 1.0), l],
+            // `s * (1 - s)` vanishes for grays and for fully saturated colors, and peaks between
+            // them, so muted colors gain the most while vivid ones barely change
+            ColorGrade::Vibrance(amount) => [h, s + amount * s * (1.0 - s), l],
+        }
+    }
+}
@@ -0,0 +1,64 @@
+/////////////////////////////
+// unit tests for grade.rs //
+/////////////////////////////
+use super::*;
+use crate::image::{ColorImage, GrayImage, ImageManip};
+
+fn colors() -> ColorImage {
+    ColorImage {
+        width: 4,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![255, 0, 100, 200],
+        gpixels: vec![0, 255, 100, 150],
+        bpixels: vec![0, 0, 100, 100],
+    }
+}
+
+#[test]
+fn rotate_hue() {
+    assert_eq!(ColorGrade::Hue(90.0).apply([300.0, 0.5, 0.5]), [30.0, 0.5, 0.5]);
+    assert_eq!(ColorGrade::Hue(-90.0).apply([30.0, 0.5, 0.5]), [300.0, 0.5, 0.5]);
+
+    let out = colors().grade(&ColorGrade::Hue(120.0));
+    assert_eq!(out.rpixels, vec![0, 0, 100, 100]);
+    assert_eq!(out.gpixels, vec![255, 0, 100, 200]);
+    assert_eq!(out.bpixels, vec![0, 255, 100, 150]);
+
+    // a full turn changes nothing
+    assert_eq!(colors().grade(&ColorGrade::Hue(360.0)), colors());
+}
+
+#[test]
+fn scale_saturation() {
+    assert_eq!(ColorGrade::Saturation(1.5).apply([0.0, 0.5, 0.5]), [0.0, 0.75, 0.5]);
+    assert_eq!(ColorGrade::Saturation(3.0).apply([0.0, 0.5, 0.5]), [0.0, 1.0, 0.5]);
+
+    let out = colors().grade(&ColorGrade::Saturation(0.0));
+    assert_eq!(out.rpixels, vec![128, 128, 100, 150]);
+    assert_eq!(out.rpixels, out.gpixels);
+    assert_eq!(out.rpixels, out.bpixels);
+    assert_eq!(colors().grade(&ColorGrade::Saturation(1.0)), colors());
+}
+
+#[test]
+fn vibrance_favors_muted_colors() {
+    let boost = |s| ColorGrade::Vibrance(1.0).apply([0.0, s, 0.5])[1] - s;
+    assert_eq!(boost(0.0), 0.0);
+    assert_eq!(boost(1.0), 0.0);
+    assert!(boost(0.3) > boost(0.8));
+    assert_eq!(ColorGrade::Vibrance(-1.0).apply([0.0, 0.5, 0.5]), [0.0, 0.25, 0.5]);
+
+    // the muted pixel gains saturation, while the pure ones and the gray keep theirs
+    let out = colors().grade(&ColorGrade::Vibrance(1.0));
+    assert_eq!(out.rpixels, vec![255, 0, 100, 226]);
+    assert_eq!(out.gpixels, vec![0, 255, 100, 150]);
+    assert_eq!(out.bpixels, vec![0, 0, 100, 74]);
+}
+
+#[test]
+fn grays_are_unchanged() {
+    let gray = GrayImage { width: 2, height: 1, maxval: 15, pixels: vec![3, 12] };
+    assert_eq!(gray.grade(&ColorGrade::Saturation(2.0)), gray);
+    assert_eq!(gray.grade(&ColorGrade::Hue(45.0)), gray);
+}
@@ -0,0 +1,72 @@
+use std::str::FromStr;
+
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// Weights of the red, green and blue channels when converting color to grayscale.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct GrayWeights(pub [f64; 3]);
+
+impl GrayWeights {
+    /// The weights the README specifies for `-g`
+    pub const README: GrayWeights = GrayWeights([0.3, 0.6, 0.1]);
+    /// ITU-R BT.601 luma (standard-definition video, and JPEG)
+    pub const BT601: GrayWeights = GrayWeights([0.299, 0.587, 0.114]);
+    /// ITU-R BT.709 luma (high-definition video, and sRGB)
+    pub const BT709: GrayWeights = GrayWeights([0.2126, 0.7152, 0.0722]);
+    /// An unweighted average of the channels
+    pub const AVERAGE: GrayWeights = GrayWeights([1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0]);
+    pub const RED: GrayWeights = GrayWeights([1.0, 0.0, 0.0]);
+    pub const GREEN: GrayWeights = GrayWeights([0.0, 1.0, 0.0]);
+    pub const BLUE: GrayWeights = GrayWeights([0.0, 0.0, 1.0]);
+
+    /// Creates weights in the given proportions, scaled so they sum to one.
+    ///
+    /// Returns `None` if any weight is negative or they're all zero.
+    pub fn custom(r: f64, g: f64, b: f64) -> Option<GrayWeights> {
+        let sum = r + g + b;
+        if r < 0.0 || g < 0.0 || b < 0.0 || sum <= 0.0 || !sum.is_finite() {
+            return None;
+        }
+        Some(GrayWeights([r / sum, g / sum, b / sum]))
+    }
+
+    /// Computes the (unrounded) weighted sum of the three planes at every pixel.
+    pub fn luminance(&self, r: &[PxVal], g: &[PxVal], b: &[PxVal]) -> Vec<f64> {
+        let [wr, wg, wb] = self.0;
+        (0..r.len())
+            .map(|i| wr * r[i] as f64 + wg * g[i] as f64 + wb * b[i] as f64)
+            .collect()
+    }
+}
+
+impl FromStr for GrayWeights {
+    type Err = String;
+
+    /// Parses a preset name (`readme`, `bt601`, `bt709`, `average`, `red`, `green` or `blue`), or
+    /// custom proportions given as `r:g:b`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "readme" => Ok(GrayWeights::README),
+            "bt601" => Ok(GrayWeights::BT601),
+            "bt709" => Ok(GrayWeights::BT709),
+            "average" => Ok(GrayWeights::AVERAGE),
+            "red" => Ok(GrayWeights::RED),
+            "green" => Ok(GrayWeights::GREEN),
+            "blue" => Ok(GrayWeights::BLUE),
+            custom => {
+                let weights = custom.split(':')
+                    .map(|w| w.parse::<f64>().ok().filter(|w| w.is_finite()))
+                    .collect::<Option<Vec<f64>>>();
+                match weights.as_deref() {
+                    Some(&[r, g, b]) => GrayWeights::custom(r, g, b),
+                    _ => None,
+                }
+                .ok_or_else(|| format!("Unknown grayscale weights '{}'", s))
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+////////////////////////////
+// unit tests for gray.rs //
+////////////////////////////
+use super::*;
+use crate::image::{ColorImage, GrayImage, ImageManip};
+
+fn make_color_image() -> ColorImage {
+    ColorImage {
+        width: 2,
+        height: 2,
+        maxval: 1023,
+        rpixels: vec![1000, 0, 0, 10],
+        gpixels: vec![0, 1000, 0, 20],
+        bpixels: vec![0, 0, 1000, 30],
+    }
+}
+
+#[test]
+fn presets_sum_to_one() {
+    for w in &[
+        GrayWeights::README,
+        GrayWeights::BT601,
+        GrayWeights::BT709,
+        GrayWeights::AVERAGE,
+        GrayWeights::GREEN,
+    ] {
+        assert!((w.0.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn readme_weights_by_default() {
+    let gray = make_color_image().grayscale();
+    assert_eq!(gray.pixels, vec![300, 600, 100, 18]);
+    assert_eq!(gray.maxval, 1023);
+    assert_eq!((gray.width, gray.height), (2, 2));
+}
+
+#[test]
+fn bt709_weights() {
+    let gray = make_color_image().grayscale_with(&GrayWeights::BT709);
+    assert_eq!(gray.pixels, vec![213, 715, 72, 19]);
+}
+
+#[test]
+fn rounds_to_nearest() {
+    // 0.3 * 5 = 1.5 rounds up, where truncating would give 1
+    let img = ColorImage { width: 1, height: 1, maxval: 15, rpixels: vec![5], gpixels: vec![0], bpixels: vec![0] };
+    assert_eq!(img.grayscale().pixels, vec![2]);
+}
+
+#[test]
+fn single_channel_extraction() {
+    let gray = make_color_image().grayscale_with(&GrayWeights::GREEN);
+    assert_eq!(gray.pixels, make_color_image().gpixels);
+}
+
+#[test]
+fn gray_stays_gray() {
+    let img = GrayImage { width: 2, height: 1, maxval: 255, pixels: vec![3, 4] };
+    assert_eq!(img.grayscale_with(&GrayWeights::BT601), img);
+}
+
+#[test]
+fn parse_weights() {
+    assert_eq!("bt601".parse::<GrayWeights>(), Ok(GrayWeights::BT601));
+    assert_eq!("blue".parse::<GrayWeights>(), Ok(GrayWeights::BLUE));
+    assert_eq!("2:1:1".parse::<GrayWeights>(), Ok(GrayWeights([0.5, 0.25, 0.25])));
+    assert!("0:0:0".parse::<GrayWeights>().is_err());
+    assert!("1:-1:1".parse::<GrayWeights>().is_err());
+    assert!("1:1".parse::<GrayWeights>().is_err());
+    assert!("1:x:1:1".parse::<GrayWeights>().is_err());
+    assert!("1:1:1:junk".parse::<GrayWeights>().is_err());
+    assert!("1:inf:1".parse::<GrayWeights>().is_err());
+    assert!("nan:1:1".parse::<GrayWeights>().is_err());
+    assert!("sepia".parse::<GrayWeights>().is_err());
+}
@@ -0,0 +1,111 @@
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// The tonal distribution of one channel of an image.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct ChannelStats {
+    pub name: &'static str,
+    /// How many values fall into each bin, from darkest to lightest
+    pub bins: Vec<usize>,
+    pub min: PxVal,
+    pub max: PxVal,
+    pub mean: f64,
+    /// The lower median (for an even number of values, the smaller of the middle two)
+    pub median: PxVal,
+    /// Population standard deviation
+    pub stddev: f64,
+}
+
+/// The tonal distribution of every channel of an image.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Histogram {
+    pub width: usize,
+    pub height: usize,
+    pub maxval: usize,
+    pub channels: Vec<ChannelStats>,
+}
+
+/// Counts how many times each value from 0 to `maxval` appears in a plane.
+pub fn counts(pixels: &[PxVal], maxval: usize) -> Vec<usize> {
+    let mut counts = vec![0; maxval + 1];
+    for &v in pixels {
+        counts[(v as usize).min(maxval)] += 1;
+    }
+    counts
+}
+
+impl ChannelStats {
+    /// Computes the statistics of a plane.
+    ///
+    /// With `bins` of `None`, there is a bin for every value from 0 to `maxval`. Otherwise, the
+    /// range is split into that many equal-width bins.
+    pub fn new(name: &'static str, pixels: &[PxVal], maxval: usize, bins: Option<usize>) -> ChannelStats {
+        let counts = counts(pixels, maxval);
+        let n = pixels.len().max(1) as f64;
+
+        let min = counts.iter().position(|&c| c > 0).unwrap_or(0) as PxVal;
+        let max = counts.iter().rposition(|&c| c > 0).unwrap_or(0) as PxVal;
+        let mean = pixels.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let variance = pixels.iter().map(|&v| (v as f64 - mean).powi(2)).sum::<f64>() / n;
+
+        let half = pixels.len().saturating_sub(1) / 2;
+        let mut seen = 0;
+        let mut median = 0;
+        for (v, &c) in counts.iter().enumerate() {
+            seen += c;
+            if seen > half {
+                median = v as PxVal;
+                break;
+            }
+        }
+
+        let bins = match bins {
+            None => counts,
+            Some(n) => {
+                let mut binned = vec![0; n];
+                for (v, c) in counts.into_iter().enumerate() {
+                    binned[v * n / (maxval + 1)] += c;
+                }
+                binned
+            }
+        };
+
+        ChannelStats { name, bins, min, max, mean, median, stddev: variance.sqrt() }
+    }
+}
+
+impl Histogram {
+    /// Formats the histogram as human-readable text, one line of statistics and one line of bin
+    /// counts per channel.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}x{}, maxval {}\n", self.width, self.height, self.maxval);
+        for c in &self.channels {
+            text += &format!(
+                "{}: min {} max {} mean {:.3} median {} stddev {:.3}\n",
+                c.name, c.min, c.max, c.mean, c.median, c.stddev
+            );
+            let bins: Vec<String> = c.bins.iter().map(|b| b.to_string()).collect();
+            text += &format!("{} bins: {}\n", c.name, bins.join(" "));
+        }
+        text
+    }
+
+    /// Formats the histogram as a JSON object.
+    pub fn to_json(&self) -> String {
+        let channels: Vec<String> = self.channels.iter()
+            .map(|c| {
+                let bins: Vec<String> = c.bins.iter().map(|b| b.to_string()).collect();
+                format!(
+                    "{{\"name\":\"{}\",\"min\":{},\"max\":{},\"mean\":{},\"median\":{},\"stddev\":{},\"bins\":[{}]}}",
+                    c.name, c.min, c.max, c.mean, c.median, c.stddev, bins.join(",")
+                )
+            })
+            .collect();
+        format!(
+            "{{\"width\":{},\"height\":{},\"maxval\":{},\"channels\":[{}]}}\n",
+            self.width, self.height, self.maxval, channels.join(",")
+        )
+    }
+}
@@ -0,0 +1,79 @@
+/////////////////////////////////
+// unit tests for histogram.rs //
+/////////////////////////////////
+use super::*;
+use crate::image::{ColorImage, Image};
+
+#[test]
+fn full_resolution_counts() {
+    let stats = ChannelStats::new("gray", &[0, 3, 3, 15], 15, None);
+    assert_eq!(stats.bins.len(), 16);
+    assert_eq!(stats.bins[3], 2);
+    assert_eq!(stats.bins[15], 1);
+    assert_eq!(stats.bins.iter().sum::<usize>(), 4);
+}
+
+#[test]
+fn binned_counts() {
+    let pixels: Vec<PxVal> = (0..=255).collect();
+    let stats = ChannelStats::new("gray", &pixels, 255, Some(4));
+    assert_eq!(stats.bins, vec![64, 64, 64, 64]);
+
+    let stats = ChannelStats::new("gray", &[0, 127, 128, 255], 255, Some(2));
+    assert_eq!(stats.bins, vec![2, 2]);
+}
+
+#[test]
+fn summary_statistics() {
+    let stats = ChannelStats::new("gray", &[2, 4, 4, 4, 5, 5, 7, 9], 15, None);
+    assert_eq!(stats.min, 2);
+    assert_eq!(stats.max, 9);
+    assert_eq!(stats.mean, 5.0);
+    assert_eq!(stats.median, 4);
+    assert_eq!(stats.stddev, 2.0);
+}
+
+#[test]
+fn color_histogram_has_three_channels() {
+    let img = Image::from(ColorImage {
+        width: 2,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![1, 2],
+        gpixels: vec![3, 4],
+        bpixels: vec![5, 6],
+    });
+    let hist = img.histogram(None);
+    let names: Vec<&str> = hist.channels.iter().map(|c| c.name).collect();
+    assert_eq!(names, vec!["red", "green", "blue"]);
+    assert_eq!(hist.channels[2].max, 6);
+}
+
+#[test]
+fn json_report() {
+    let hist = Histogram {
+        width: 2,
+        height: 1,
+        maxval: 3,
+        channels: vec![ChannelStats::new("gray", &[1, 3], 3, None)],
+    };
+    assert_eq!(
+        hist.to_json(),
+        "{\"width\":2,\"height\":1,\"maxval\":3,\"channels\":[{\"name\":\"gray\",\"min\":1,\
+         \"max\":3,\"mean\":2,\"median\":1,\"stddev\":1,\"bins\":[0,1,0,1]}]}\n"
+    );
+}
+
+#[test]
+fn text_report() {
+    let hist = Histogram {
+        width: 2,
+        height: 1,
+        maxval: 3,
+        channels: vec![ChannelStats::new("gray", &[1, 3], 3, None)],
+    };
+    assert_eq!(
+        hist.to_text(),
+        "2x1, maxval 3\ngray: min 1 max 3 mean 2.000 median 1 stddev 1.000\ngray bins: 0 1 0 1\n"
+    );
+}
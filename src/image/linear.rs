@@ -0,0 +1,74 @@
+use crate::args::ManipOption;
+use super::{ChannelSource, PaletteSource, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// The maximum value of linear-light samples in the working space.
+///
+/// Decoding spreads the darkest values of an 8-bit image far apart, so linear samples are held
+/// with 16 bits of precision regardless of the source's `maxval`. (Every operation still does
+/// its own arithmetic in floating point.)
+pub const LINEAR_MAXVAL: usize = 65535;
+
+/// Converts a BT.709 gamma-encoded value (from 0 to 1) into linear light.
+///
+/// Netpbm specifies that PPM and PGM samples are encoded this way.
+pub fn decode(v: f64) -> f64 {
+    if v < 0.081 {
+        v / 4.5
+    } else {
+        ((v + 0.099) / 1.099).powf(1.0 / 0.45)
+    }
+}
+
+/// Converts linear light (from 0 to 1) into a BT.709 gamma-encoded value.
+pub fn encode(l: f64) -> f64 {
+    if l < 0.018 {
+        4.5 * l
+    } else {
+        1.099 * l.powf(0.45) - 0.099
+    }
+}
+
+/// Builds a table mapping `0..=from` through `f` onto `0..=to`.
+fn lut(from: usize, to: usize, f: fn(f64) -> f64) -> Vec<PxVal> {
+    (0..=from)
+        .map(|v| (f(v as f64 / from as f64) * to as f64).round().clamp(0.0, to as f64) as PxVal)
+        .collect()
+}
+
+/// Builds a table decoding samples from `0..=maxval` into linear samples up to [`LINEAR_MAXVAL`].
+pub fn decode_lut(maxval: usize) -> Vec<PxVal> {
+    lut(maxval, LINEAR_MAXVAL, decode)
+}
+
+/// Builds a table encoding linear samples up to [`LINEAR_MAXVAL`] into samples from `0..=maxval`.
+pub fn encode_lut(maxval: usize) -> Vec<PxVal> {
+    lut(LINEAR_MAXVAL, maxval, encode)
+}
+
+/// Whether `op` can be applied in linear light.
+///
+/// Its result must either be a tonal image at the working maxval, which is encoded again, or a
+/// bilevel one (e.g. from thresholding), which is kept as it is. Options whose results aren't
+/// light at all (labels, distances, edge strengths, ...), which reduce to a chosen maxval, which
+/// load other gamma-encoded images, or which convert color spaces themselves are refused.
+pub fn supports(op: &ManipOption) -> bool {
+    match op {
+        ManipOption::Edges(_)
+        | ManipOption::Emboss(_)
+        | ManipOption::Dither(_)
+        | ManipOption::Label(_)
+        | ManipOption::Distance(_)
+        | ManipOption::Skeleton
+        | ManipOption::Component { .. }
+        | ManipOption::InComponent { .. }
+        | ManipOption::Linear(_) => false,
+        ManipOption::Combine(sources) => {
+            !sources.iter().any(|source| matches!(source, ChannelSource::File(_)))
+        }
+        ManipOption::Quantize(params) => !matches!(params.palette, PaletteSource::File(_)),
+        _ => true,
+    }
+}
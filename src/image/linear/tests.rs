@@ -0,0 +1,104 @@
+//////////////////////////////
+// unit tests for linear.rs //
+//////////////////////////////
+use super::*;
+use crate::args::ManipOption;
+use crate::image::{ChannelSource, GrayImage, Image, ImageType};
+
+/// A black and white checkerboard of single pixels
+fn checkerboard(size: usize) -> Image {
+    Image::from(GrayImage {
+        width: size,
+        height: size,
+        maxval: 255,
+        pixels: (0..size * size)
+            .map(|i| if (i % size + i / size).is_multiple_of(2) { 0 } else { 255 })
+            .collect(),
+    })
+}
+
+fn pixels(img: &Image) -> &[PxVal] {
+    match &img.0 {
+        ImageType::Grayscale(img) => &img.pixels,
+        ImageType::Color(img) => &img.rpixels,
+    }
+}
+
+#[test]
+fn transfer_functions_invert() {
+    for i in 0..=100 {
+        let v = i as f64 / 100.0;
+        assert!((encode(decode(v)) - v).abs() < 1e-9);
+    }
+    assert_eq!(decode(0.0), 0.0);
+    assert!((decode(1.0) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn eight_bit_round_trip_is_lossless() {
+    let decoded = decode_lut(255);
+    let encoded = encode_lut(255);
+    for v in 0..=255 {
+        assert_eq!(encoded[decoded[v] as usize] as usize, v);
+    }
+}
+
+#[test]
+fn linear_do_nothing_round_trips() {
+    let img = checkerboard(4);
+    let out = img.manipulate(&ManipOption::Linear(Box::new(ManipOption::DoNothing))).unwrap();
+    assert_eq!(out, img);
+}
+
+#[test]
+fn checkerboard_downscale() {
+    let img = checkerboard(8);
+
+    // averaging gamma-encoded values gives code 128, which displays far darker than the
+    // checkerboard appears from a distance
+    let gamma = img.manipulate(&ManipOption::Downscale(2)).unwrap();
+    assert!(pixels(&gamma).iter().all(|&v| v == 128));
+
+    // averaging in linear light gives half the light, which encodes to a much higher code
+    let linear = img.manipulate(&ManipOption::Linear(Box::new(ManipOption::Downscale(2)))).unwrap();
+    assert!(pixels(&linear).iter().all(|&v| v == 180));
+    assert!((decode(180.0 / 255.0) - 0.5).abs() < 0.005);
+}
+
+#[test]
+fn bilevel_results_stay_bilevel() {
+    let img = checkerboard(8);
+    let canny = crate::image::Canny { sigma: 1.0, thresholds: None };
+    let out = img.manipulate(&ManipOption::Linear(Box::new(ManipOption::Canny(canny)))).unwrap();
+    match out.0 {
+        ImageType::Grayscale(img) => assert_eq!(img.maxval, 1),
+        _ => panic!("expected a grayscale image"),
+    }
+}
+
+#[test]
+fn non_tonal_results_refused() {
+    let img = checkerboard(8);
+    for op in [
+        ManipOption::Label(crate::image::Connectivity::Eight),
+        ManipOption::Distance(255),
+        ManipOption::Dither(crate::image::Dither { method: crate::image::DitherMethod::Bayer(2), maxval: 15 }),
+        ManipOption::Combine([
+            ChannelSource::Channel(crate::image::Channel::Red),
+            ChannelSource::File("x.pgm".to_string()),
+            ChannelSource::Channel(crate::image::Channel::Blue),
+        ]),
+    ] {
+        let err = img.manipulate(&ManipOption::Linear(Box::new(op))).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn thresholds_are_not_encoded() {
+    // half the light is code 180, so it falls below a linear threshold of 0.6
+    let img = Image::from(GrayImage { width: 2, height: 1, maxval: 255, pixels: vec![180, 255] });
+    let op = ManipOption::Threshold(crate::image::Threshold::Fixed(0.6));
+    let out = img.manipulate(&ManipOption::Linear(Box::new(op))).unwrap();
+    assert_eq!(pixels(&out), &[0, 1]);
+}
@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use super::convolve::EdgeMode;
+use super::{Image, ImageType, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// A morphological operation, built from the minimum (erosion) and maximum (dilation) of the
+/// values under a structuring element.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum MorphOp {
+    Erode,
+    Dilate,
+    /// Erode then dilate, removing bright details smaller than the element
+    Open,
+    /// Dilate then erode, filling dark details smaller than the element
+    Close,
+    /// The image minus its opening, leaving only the bright details
+    TopHat,
+    /// The closing minus the image, leaving only the dark details
+    BlackHat,
+    /// The dilation minus the erosion, outlining edges
+    Gradient,
+}
+
+/// The shape of a structuring element, as given on the command line.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Shape {
+    /// Every offset within `radius` rows and columns
+    Square(usize),
+    /// The center row and column, out to `radius`
+    Cross(usize),
+    /// Every offset within a euclidean distance of `radius`
+    Disk(usize),
+    /// The black pixels of a PBM file, centered on its middle pixel
+    File(String),
+}
+
+/// The set of offsets a morphological operation looks at around each pixel.
+///
+/// Offsets are stored as horizontal runs, which lets erosion and dilation slide along rows
+/// rather than visit every offset separately.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct StructuringElement {
+    /// `(dy, x0, x1)`: every offset from `(x0, dy)` to `(x1, dy)`, inclusive
+    runs: Vec<(isize, isize, isize)>,
+}
+
+impl StructuringElement {
+    /// Builds the element of a [`Shape`], loading it if it comes from a file.
+    pub fn new(shape: &Shape) -> io::Result<StructuringElement> {
+        let runs = match *shape {
+            Shape::Square(radius) => {
+                let r = radius as isize;
+                (-r..=r).map(|dy| (dy, -r, r)).collect()
+            }
+            Shape::Cross(radius) => {
+                let r = radius as isize;
+                (-r..=r).map(|dy| if dy == 0 { (0, -r, r) } else { (dy, 0, 0) }).collect()
+            }
+            Shape::Disk(radius) => {
+                let r = radius as isize;
+                (-r..=r)
+                    .map(|dy| {
+                        let reach = ((r * r - dy * dy) as f64).sqrt().floor() as isize;
+                        (dy, -reach, reach)
+                    })
+                    .collect()
+            }
+            Shape::File(ref path) => return StructuringElement::load(path),
+        };
+        Ok(StructuringElement { runs })
+    }
+
+    /// Loads an element from a PBM file, whose black pixels (`1`s in the file) are the members.
+    ///
+    /// The origin is the middle pixel, rounding down and to the right for even sizes.
+    pub fn load(path: &str) -> io::Result<StructuringElement> {
+        let img = match Image::load(path)?.0 {
+            ImageType::Grayscale(img) if img.maxval == 1 => img,
+            _ => return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A structuring element must be a bilevel image (PBM)"
+            )),
+        };
+        let members: Vec<bool> = img.pixels.iter().map(|&v| v == 0).collect();
+        StructuringElement::from_mask(&members, img.width, img.height)
+    }
+
+    /// Builds an element from a `width` by `height` grid of flags centered on its middle.
+    pub fn from_mask(mask: &[bool], width: usize, height: usize) -> io::Result<StructuringElement> {
+        let (cx, cy) = ((width / 2) as isize, (height / 2) as isize);
+        let mut runs = Vec::new();
+        for (y, row) in mask.chunks(width.max(1)).enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if !row[x] {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < row.len() && row[x] {
+                    x += 1;
+                }
+                runs.push((y as isize - cy, start as isize - cx, x as isize - 1 - cx));
+            }
+        }
+
+        if runs.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Structuring element is empty"));
+        }
+        Ok(StructuringElement { runs })
+    }
+
+    /// The element mirrored through its origin, which dilation looks through.
+    fn reflect(&self) -> StructuringElement {
+        StructuringElement {
+            runs: self.runs.iter().map(|&(dy, x0, x1)| (-dy, -x1, -x0)).collect(),
+        }
+    }
+
+    /// Replaces every value with the minimum of the values under the element.
+    pub fn erode(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        self.extreme(pixels, width, height, maxval, false)
+    }
+
+    /// Replaces every value with the maximum of the values under the reflected element.
+    pub fn dilate(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        self.reflect().extreme(pixels, width, height, maxval, true)
+    }
+
+    /// Takes the minimum (or maximum) over the element, with neighbors which fall off an edge
+    /// taking the value of the nearest pixel on it.
+    ///
+    /// Bilevel planes are packed 64 pixels to a word, so the minimum and maximum become bitwise
+    /// and/or of whole words.
+    fn extreme(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize, max: bool) -> Vec<PxVal> {
+        if width == 0 || height == 0 {
+            return Vec::new();
+        }
+        if maxval == 1 {
+            return self.extreme_bits(pixels, width, height, max);
+        }
+
+        let pick = |a: PxVal, b: PxVal| if max { a.max(b) } else { a.min(b) };
+        let mut out = vec![if max { 0 } else { PxVal::MAX }; pixels.len()];
+
+        // every row only needs sliding along once for each distinct span of offsets
+        let mut spans: BTreeMap<(isize, isize), Vec<isize>> = BTreeMap::new();
+        for &(dy, x0, x1) in &self.runs {
+            spans.entry((x0, x1)).or_default().push(dy);
+        }
+        for ((x0, x1), dys) in spans {
+            let slid: Vec<PxVal> = pixels.chunks(width)
+                .flat_map(|row| slide(row, x0, x1, pick))
+                .collect();
+            for dy in dys {
+                for (y, out_row) in out.chunks_mut(width).enumerate() {
+                    let sy = EdgeMode::Replicate.resolve(y as isize + dy, height).unwrap();
+                    for (o, &v) in out_row.iter_mut().zip(&slid[sy * width..(sy + 1) * width]) {
+                        *o = pick(*o, v);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// The bilevel version of [`StructuringElement::extreme`].
+    fn extreme_bits(&self, pixels: &[PxVal], width: usize, height: usize, max: bool) -> Vec<PxVal> {
+        let words = width.div_ceil(64);
+        let packed: Vec<Vec<u64>> = pixels.chunks(width).map(pack).collect();
+        let mut out = vec![vec![if max { 0 } else { !0 }; words]; height];
+
+        // every row only needs shifting once for each distinct horizontal offset
+        let mut columns: BTreeMap<isize, Vec<isize>> = BTreeMap::new();
+        for &(dy, x0, x1) in &self.runs {
+            for dx in x0..=x1 {
+                columns.entry(dx).or_default().push(dy);
+            }
+        }
+        for (dx, dys) in columns {
+            let shifted: Vec<Vec<u64>> = packed.iter().map(|row| shift(row, width, dx)).collect();
+            for dy in dys {
+                for (y, out_row) in out.iter_mut().enumerate() {
+                    let sy = EdgeMode::Replicate.resolve(y as isize + dy, height).unwrap();
+                    for (o, &v) in out_row.iter_mut().zip(&shifted[sy]) {
+                        *o = if max { *o | v } else { *o & v };
+                    }
+                }
+            }
+        }
+        out.iter().flat_map(|row| unpack(row, width)).collect()
+    }
+}
+
+impl MorphOp {
+    /// Applies the operation to a single plane of pixels.
+    ///
+    /// The foreground of a bilevel plane is black, as it is in a PBM file, so such planes are
+    /// inverted on the way in and out: dilation grows the black pixels, and opening removes black
+    /// specks.
+    pub fn apply(
+        &self,
+        element: &StructuringElement,
+        pixels: &[PxVal],
+        width: usize,
+        height: usize,
+        maxval: usize,
+    ) -> Vec<PxVal> {
+        if maxval == 1 {
+            let invert = |p: &[PxVal]| p.iter().map(|&v| 1 - v.min(1)).collect::<Vec<PxVal>>();
+            return invert(&self.apply_values(element, &invert(pixels), width, height, maxval));
+        }
+        self.apply_values(element, pixels, width, height, maxval)
+    }
+
+    /// Applies the operation to the values of a plane, whatever its maxval.
+    fn apply_values(
+        &self,
+        element: &StructuringElement,
+        pixels: &[PxVal],
+        width: usize,
+        height: usize,
+        maxval: usize,
+    ) -> Vec<PxVal> {
+        let erode = |p: &[PxVal]| element.erode(p, width, height, maxval);
+        let dilate = |p: &[PxVal]| element.dilate(p, width, height, maxval);
+        match self {
+            MorphOp::Erode => erode(pixels),
+            MorphOp::Dilate => dilate(pixels),
+            MorphOp::Open => dilate(&erode(pixels)),
+            MorphOp::Close => erode(&dilate(pixels)),
+            MorphOp::TopHat => difference(pixels, &dilate(&erode(pixels))),
+            MorphOp::BlackHat => difference(&erode(&dilate(pixels)), pixels),
+            MorphOp::Gradient => difference(&dilate(pixels), &erode(pixels)),
+        }
+    }
+}
+
+/// Subtracts one plane from another, stopping at zero.
+fn difference(a: &[PxVal], b: &[PxVal]) -> Vec<PxVal> {
+    a.iter().zip(b).map(|(a, b)| a.saturating_sub(*b)).collect()
+}
+
+/// Finds the minimum (or maximum, depending on `pick`) of `row[x + x0..=x + x1]` for every `x`,
+/// replicating the ends of the row.
+///
+/// This is the van Herk/Gil-Werman algorithm: the padded row is cut into blocks as long as the
+/// window, so that every window is the end of one block and the start of the next, and running
+/// extremes from either side of each block give every window in a constant number of steps.
+fn slide<F>(row: &[PxVal], x0: isize, x1: isize, pick: F) -> Vec<PxVal>
+where
+    F: Fn(PxVal, PxVal) -> PxVal,
+{
+    let len = (x1 - x0 + 1) as usize;
+    let padded: Vec<PxVal> = (0..row.len() + len - 1)
+        .map(|i| row[EdgeMode::Replicate.resolve(i as isize + x0, row.len()).unwrap()])
+        .collect();
+
+    let mut forward = padded.clone();
+    for i in 1..forward.len() {
+        if i % len != 0 {
+            forward[i] = pick(forward[i - 1], forward[i]);
+        }
+    }
+    let mut backward = padded;
+    for i in (0..backward.len().saturating_sub(1)).rev() {
+        if (i + 1) % len != 0 {
+            backward[i] = pick(backward[i + 1], backward[i]);
+        }
+    }
+
+    (0..row.len()).map(|x| pick(backward[x], forward[x + len - 1])).collect()
+}
+
+/// Packs a row of bilevel pixels into words, least significant bit first.
+fn pack(row: &[PxVal]) -> Vec<u64> {
+    row.chunks(64)
+        .map(|chunk| chunk.iter()
+            .enumerate()
+            .filter(|(_, &v)| v != 0)
+            .fold(0, |word, (i, _)| word | (1 << i)))
+        .collect()
+}
+
+/// Unpacks a row of `width` bilevel pixels from words.
+fn unpack(row: &[u64], width: usize) -> Vec<PxVal> {
+    (0..width).map(|x| ((row[x / 64] >> (x % 64)) & 1) as PxVal).collect()
+}
+
+/// Shifts a packed row so that pixel `x` holds what was at `x + dx`, replicating the ends.
+fn shift(row: &[u64], width: usize, dx: isize) -> Vec<u64> {
+    let bit = |x: usize| (row[x / 64] >> (x % 64)) & 1 != 0;
+    let (words, bits) = (dx.unsigned_abs() / 64, (dx.unsigned_abs() % 64) as u32);
+    let word = |i: isize| if (0..row.len() as isize).contains(&i) { row[i as usize] } else { 0 };
+
+    let mut out: Vec<u64> = (0..row.len() as isize)
+        .map(|i| {
+            if dx >= 0 {
+                let (lo, hi) = (word(i + words as isize), word(i + words as isize + 1));
+                if bits == 0 { lo } else { (lo >> bits) | (hi << (64 - bits)) }
+            } else {
+                let (hi, lo) = (word(i - words as isize), word(i - words as isize - 1));
+                if bits == 0 { hi } else { (hi << bits) | (lo >> (64 - bits)) }
+            }
+        })
+        .collect();
+
+    // the pixels shifted in from past either end repeat the pixel at that end
+    let (fill, edge) = if dx >= 0 {
+        (width.saturating_sub(dx as usize)..width, bit(width - 1))
+    } else {
+        (0..dx.unsigned_abs().min(width), bit(0))
+    };
+    for x in fill {
+        if edge {
+            out[x / 64] |= 1 << (x % 64);
+        } else {
+            out[x / 64] &= !(1 << (x % 64));
+        }
+    }
+
+    // keep the padding past the end of the row clear
+    if !width.is_multiple_of(64) {
+        *out.last_mut().unwrap() &= (1 << (width % 64)) - 1;
+    }
+    out
+}
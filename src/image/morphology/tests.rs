@@ -0,0 +1,205 @@
+//////////////////////////////////
+// unit tests for morphology.rs //
+//////////////////////////////////
+use super::*;
+use crate::image::tests::img_folder;
+use crate::image::{GrayImage, ImageManip, RankFilter, Window};
+
+fn element(shape: Shape) -> StructuringElement {
+    StructuringElement::new(&shape).unwrap()
+}
+
+/// Takes the minimum or maximum over every offset of an element, one pixel at a time
+fn brute_force(se: &StructuringElement, pixels: &[PxVal], width: usize, height: usize, max: bool) -> Vec<PxVal> {
+    let clamp = |v: isize, len: usize| v.max(0).min(len as isize - 1) as usize;
+    let mut out = Vec::new();
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let values = se.runs.iter().flat_map(|&(dy, x0, x1)| {
+                (x0..=x1).map(move |dx| {
+                    // dilation looks through the element mirrored
+                    let (dx, dy) = if max { (-dx, -dy) } else { (dx, dy) };
+                    pixels[clamp(y + dy, height) * width + clamp(x + dx, width)]
+                })
+            });
+            out.push(if max { values.max().unwrap() } else { values.min().unwrap() });
+        }
+    }
+    out
+}
+
+/// Deterministic pseudo-random pixels
+fn noise(len: usize, maxval: usize) -> Vec<PxVal> {
+    let mut state: u32 = 2024;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            ((state >> 8) as usize % (maxval + 1)) as PxVal
+        })
+        .collect()
+}
+
+#[test]
+fn shapes() {
+    assert_eq!(element(Shape::Square(1)).runs, vec![(-1, -1, 1), (0, -1, 1), (1, -1, 1)]);
+    assert_eq!(element(Shape::Cross(1)).runs, vec![(-1, 0, 0), (0, -1, 1), (1, 0, 0)]);
+    assert_eq!(
+        element(Shape::Disk(2)).runs,
+        vec![(-2, 0, 0), (-1, -1, 1), (0, -2, 2), (1, -1, 1), (2, 0, 0)]
+    );
+}
+
+#[test]
+fn element_from_mask() {
+    let mask = [
+        true, false, true, true,
+        false, false, false, false,
+        false, true, true, true,
+    ];
+    let se = StructuringElement::from_mask(&mask, 4, 3).unwrap();
+    assert_eq!(se.runs, vec![(-1, -2, -2), (-1, 0, 1), (1, -1, 1)]);
+    assert_eq!(se.reflect().runs, vec![(1, 2, 2), (1, -1, 0), (-1, -1, 1)]);
+
+    assert!(StructuringElement::from_mask(&[false; 4], 2, 2).is_err());
+}
+
+#[test]
+fn element_from_pbm() {
+    // black pixels are members, centered on the middle of the 10x3 bitmap
+    let se = element(Shape::File(img_folder() + "bits.pbm"));
+    assert_eq!(se.runs, vec![(-1, -5, -5), (-1, 4, 4), (0, -4, -3), (0, 0, 1), (0, 3, 3), (1, -5, -1)]);
+
+    assert!(StructuringElement::new(&Shape::File(img_folder() + "feep.pgm")).is_err());
+    assert!(StructuringElement::new(&Shape::File(img_folder() + "bogus.pbm")).is_err());
+}
+
+#[test]
+fn gray_matches_brute_force() {
+    let (width, height) = (13, 9);
+    let pixels = noise(width * height, 255);
+    let mask: Vec<bool> = noise(15, 1).iter().map(|&v| v == 1).collect();
+    let shapes = vec![
+        element(Shape::Square(2)),
+        element(Shape::Cross(3)),
+        element(Shape::Disk(4)),
+        StructuringElement::from_mask(&mask, 5, 3).unwrap(),
+    ];
+    for se in &shapes {
+        assert_eq!(se.erode(&pixels, width, height, 255), brute_force(se, &pixels, width, height, false));
+        assert_eq!(se.dilate(&pixels, width, height, 255), brute_force(se, &pixels, width, height, true));
+    }
+}
+
+#[test]
+fn bilevel_matches_brute_force() {
+    // wide enough for rows to span several words, with offsets crossing word boundaries
+    let (width, height) = (150, 6);
+    let pixels = noise(width * height, 1);
+    let mut mask = vec![false; 141];
+    mask[0] = true;
+    mask[70] = true;
+    mask[75] = true;
+    let shapes = vec![
+        element(Shape::Square(1)),
+        element(Shape::Disk(3)),
+        StructuringElement::from_mask(&mask, 141, 1).unwrap(),
+        element(Shape::Square(40)),
+    ];
+    for se in &shapes {
+        assert_eq!(se.erode(&pixels, width, height, 1), brute_force(se, &pixels, width, height, false));
+        assert_eq!(se.dilate(&pixels, width, height, 1), brute_force(se, &pixels, width, height, true));
+    }
+}
+
+#[test]
+fn erosion_is_minimum_filter() {
+    let pixels = noise(8 * 8, 1000);
+    let rank = RankFilter { radius: 2, percentile: 0.0, window: Window::Circle };
+    assert_eq!(
+        element(Shape::Disk(2)).erode(&pixels, 8, 8, 1000),
+        rank.apply(&pixels, 8, 8, 1000)
+    );
+}
+
+#[test]
+fn opening_and_closing() {
+    // a bright speck on the left, and a dark speck in the middle of a bright square
+    let pixels = vec![
+        0, 0, 0, 0, 0, 0, 0,
+        0, 9, 0, 5, 5, 5, 0,
+        0, 0, 0, 5, 1, 5, 0,
+        0, 0, 0, 5, 5, 5, 0,
+        0, 0, 0, 0, 0, 0, 0,
+    ];
+    let se = element(Shape::Square(1));
+    let apply = |op: MorphOp| op.apply(&se, &pixels, 7, 5, 9);
+
+    let opened = apply(MorphOp::Open);
+    assert_eq!(opened[8], 0);
+    assert_eq!(opened[7 + 3], 1);
+    let closed = apply(MorphOp::Close);
+    assert_eq!(closed[2 * 7 + 4], 5);
+
+    // the hats are whatever opening took away or closing filled in
+    let top_hat = apply(MorphOp::TopHat);
+    assert_eq!(top_hat[8], 9);
+    assert!(top_hat.iter().zip(&pixels).all(|(t, p)| t <= p));
+    let black_hat = apply(MorphOp::BlackHat);
+    assert_eq!(black_hat[2 * 7 + 4], 4);
+    assert_eq!(black_hat[8], 0);
+}
+
+#[test]
+fn gradient_outlines() {
+    let mut pixels = vec![0; 36];
+    for y in 1..5 {
+        for x in 1..5 {
+            pixels[y * 6 + x] = 1;
+        }
+    }
+    let img = GrayImage { width: 6, height: 6, maxval: 255, pixels };
+    let out = img.morphology(MorphOp::Gradient, &element(Shape::Cross(1)));
+    assert_eq!(out.pixels, vec![
+        0, 1, 1, 1, 1, 0,
+        1, 1, 1, 1, 1, 1,
+        1, 1, 0, 0, 1, 1,
+        1, 1, 0, 0, 1, 1,
+        1, 1, 1, 1, 1, 1,
+        0, 1, 1, 1, 1, 0,
+    ]);
+
+    // a bilevel image's foreground is black, so the outline is drawn in black
+    let bilevel = GrayImage { maxval: 1, pixels: img.pixels.iter().map(|v| 1 - v).collect(), ..img };
+    let out = bilevel.morphology(MorphOp::Gradient, &element(Shape::Cross(1)));
+    assert_eq!(out.pixels, vec![
+        1, 0, 0, 0, 0, 1,
+        0, 0, 0, 0, 0, 0,
+        0, 0, 1, 1, 0, 0,
+        0, 0, 1, 1, 0, 0,
+        0, 0, 0, 0, 0, 0,
+        1, 0, 0, 0, 0, 1,
+    ]);
+    assert_eq!(out.maxval, 1);
+}
+
+#[test]
+fn opening_removes_black_specks() {
+    // a PBM reads black as 0: a lone black speck, and a black square big enough to survive
+    let pixels = vec![
+        1, 1, 1, 1, 1, 1, 1,
+        1, 0, 1, 0, 0, 0, 1,
+        1, 1, 1, 0, 0, 0, 1,
+        1, 1, 1, 0, 0, 0, 1,
+        1, 1, 1, 1, 1, 1, 1,
+    ];
+    let img = GrayImage { width: 7, height: 5, maxval: 1, pixels: pixels.clone() };
+    let opened = img.morphology(MorphOp::Open, &element(Shape::Square(1)));
+    let mut want = pixels.clone();
+    want[8] = 1;
+    assert_eq!(opened.pixels, want);
+
+    // and erosion shrinks the black square down to its middle
+    let eroded = img.morphology(MorphOp::Erode, &element(Shape::Square(1)));
+    assert_eq!(eroded.pixels.iter().filter(|&&v| v == 0).count(), 1);
+    assert_eq!(eroded.pixels[2 * 7 + 4], 0);
+}
@@ -0,0 +1,173 @@
+use super::dither::Diffusion;
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// A color, as red, green and blue values
+pub type Rgb = [f64; 3];
+
+/// How to choose a palette of colors for an image.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum PaletteMethod {
+    /// Recursively split the box of the image's colors at the median of its widest channel,
+    /// until there are this many boxes, and take the mean of each
+    MedianCut(usize),
+    /// Start from the median cut palette of this many colors, then refine it with k-means
+    KMeans(usize),
+}
+
+/// Where the palette of a quantized image comes from.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum PaletteSource {
+    Generate(PaletteMethod),
+    /// Every pixel of the image read from the named file
+    File(String),
+}
+
+/// Parameters of color quantization.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Quantize {
+    pub palette: PaletteSource,
+    /// Diffuse the error of remapping each pixel to its nearest palette color, if set
+    pub dither: Option<Diffusion>,
+    /// Scan alternate rows right to left while dithering
+    pub serpentine: bool,
+}
+
+/// The most passes k-means takes to refine a palette, if it doesn't settle sooner
+const KMEANS_ITERATIONS: usize = 20;
+
+impl PaletteMethod {
+    /// Chooses a palette for the given pixel colors.
+    ///
+    /// The palette has fewer colors than asked for when the image has fewer distinct colors.
+    pub fn palette(&self, colors: &[[PxVal; 3]]) -> Vec<Rgb> {
+        match *self {
+            PaletteMethod::MedianCut(n) => median_cut(colors, n),
+            PaletteMethod::KMeans(n) => kmeans(colors, median_cut(colors, n)),
+        }
+    }
+}
+
+/// Finds the median cut palette of at most `n` colors.
+fn median_cut(colors: &[[PxVal; 3]], n: usize) -> Vec<Rgb> {
+    if colors.is_empty() {
+        return Vec::new();
+    }
+
+    let widest = |b: &[[PxVal; 3]]| -> (usize, PxVal) {
+        (0..3)
+            .map(|c| {
+                let (lo, hi) = b.iter().fold((PxVal::MAX, 0), |(lo, hi), px| (lo.min(px[c]), hi.max(px[c])));
+                (c, hi - lo)
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap()
+    };
+
+    let mut boxes = vec![colors.to_vec()];
+    while boxes.len() < n {
+        let (i, (channel, range)) = boxes.iter()
+            .map(|b| widest(b))
+            .enumerate()
+            .max_by_key(|&(_, (_, range))| range)
+            .unwrap();
+        if range == 0 {
+            break;
+        }
+
+        // split where the value changes closest to the median, so that equal colors always stay in
+        // the same box (and the palette never repeats a color)
+        let mut lower = boxes.swap_remove(i);
+        lower.sort_unstable_by_key(|px| px[channel]);
+        let mid = lower.len() / 2;
+        let split = (1..lower.len())
+            .filter(|&k| lower[k - 1][channel] != lower[k][channel])
+            .min_by_key(|&k| k.abs_diff(mid))
+            .unwrap();
+        let upper = lower.split_off(split);
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+
+    boxes.iter()
+        .map(|b| {
+            let mut sum = [0.0; 3];
+            for px in b {
+                for c in 0..3 {
+                    sum[c] += px[c] as f64;
+                }
+            }
+            sum.map(|s| s / b.len() as f64)
+        })
+        .collect()
+}
+
+/// Refines a palette with Lloyd's algorithm, moving each color to the mean of the pixels nearest
+/// it until none change color (or for at most [`KMEANS_ITERATIONS`] passes).
+///
+/// A color which no pixels are nearest stays where it is.
+fn kmeans(colors: &[[PxVal; 3]], mut palette: Vec<Rgb>) -> Vec<Rgb> {
+    let mut assignment = vec![usize::MAX; colors.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        let mut sums = vec![[0.0; 3]; palette.len()];
+        let mut counts = vec![0; palette.len()];
+        for (px, assigned) in colors.iter().zip(assignment.iter_mut()) {
+            let rgb = px.map(|v| v as f64);
+            let k = nearest(&palette, rgb);
+            changed |= *assigned != k;
+            *assigned = k;
+            counts[k] += 1;
+            for c in 0..3 {
+                sums[k][c] += rgb[c];
+            }
+        }
+        if !changed {
+            break;
+        }
+        for (k, color) in palette.iter_mut().enumerate() {
+            if counts[k] > 0 {
+                *color = sums[k].map(|s| s / counts[k] as f64);
+            }
+        }
+    }
+    palette
+}
+
+/// Finds the index of the palette color closest to `rgb` (by squared distance)
+pub fn nearest(palette: &[Rgb], rgb: Rgb) -> usize {
+    let distance = |p: &Rgb| (0..3).map(|c| (p[c] - rgb[c]).powi(2)).sum::<f64>();
+    palette.iter()
+        .enumerate()
+        .min_by(|a, b| distance(a.1).partial_cmp(&distance(b.1)).unwrap())
+        .map(|(k, _)| k)
+        .unwrap()
+}
+
+impl Quantize {
+    /// Replaces every pixel of the red, green and blue `planes` with a color from `palette`.
+    pub fn remap(&self, planes: [&[PxVal]; 3], width: usize, height: usize, palette: &[Rgb]) -> [Vec<PxVal>; 3] {
+        let mut values = planes.map(|p| p.iter().map(|&v| v as f64).collect::<Vec<f64>>());
+        let snap = |v: &mut [f64]| {
+            let color = palette[nearest(palette, [v[0], v[1], v[2]])];
+            v.copy_from_slice(&color);
+        };
+        match self.dither {
+            Some(kernel) => kernel.diffuse(&mut values, width, height, self.serpentine, snap),
+            None => {
+                let [r, g, b] = &mut values;
+                for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+                    let mut v = [*r, *g, *b];
+                    snap(&mut v);
+                    *r = v[0];
+                    *g = v[1];
+                    *b = v[2];
+                }
+            }
+        }
+        values.map(|p| p.into_iter().map(|v| v as PxVal).collect())
+    }
+}
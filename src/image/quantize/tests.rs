@@ -0,0 +1,132 @@
+////////////////////////////////
+// unit tests for quantize.rs //
+////////////////////////////////
+use super::*;
+use crate::args::ManipOption;
+use crate::image::tests::img_folder;
+use crate::image::{ColorImage, GrayImage, Image, ImageManip, ImageType};
+
+/// Two clusters of colors: reds around (200, 20, 20) and blues around (20, 20, 200)
+fn two_clusters() -> Vec<[PxVal; 3]> {
+    vec![
+        [190, 20, 20], [210, 20, 20], [200, 10, 30], [200, 30, 10],
+        [20, 20, 190], [20, 20, 210], [10, 30, 200], [30, 10, 200],
+    ]
+}
+
+fn image(colors: &[[PxVal; 3]]) -> ColorImage {
+    ColorImage {
+        width: colors.len(),
+        height: 1,
+        maxval: 255,
+        rpixels: colors.iter().map(|c| c[0]).collect(),
+        gpixels: colors.iter().map(|c| c[1]).collect(),
+        bpixels: colors.iter().map(|c| c[2]).collect(),
+    }
+}
+
+fn sorted(mut palette: Vec<Rgb>) -> Vec<Rgb> {
+    palette.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    palette
+}
+
+#[test]
+fn median_cut_finds_clusters() {
+    let palette = sorted(PaletteMethod::MedianCut(2).palette(&two_clusters()));
+    assert_eq!(palette, vec![[20.0, 20.0, 200.0], [200.0, 20.0, 20.0]]);
+    assert_eq!(PaletteMethod::MedianCut(8).palette(&two_clusters()).len(), 8);
+}
+
+#[test]
+fn palettes_stop_at_distinct_colors() {
+    let colors = vec![[5, 5, 5], [5, 5, 5], [9, 0, 0]];
+    assert_eq!(sorted(PaletteMethod::MedianCut(16).palette(&colors)), vec![[5.0, 5.0, 5.0], [9.0, 0.0, 0.0]]);
+    assert_eq!(PaletteMethod::KMeans(16).palette(&colors).len(), 2);
+    assert!(PaletteMethod::KMeans(4).palette(&[]).is_empty());
+
+    // the median falls between two equal colors, which still end up in the same box
+    let colors = vec![[5, 5, 5], [5, 5, 5], [9, 5, 5]];
+    assert_eq!(sorted(PaletteMethod::MedianCut(16).palette(&colors)), vec![[5.0, 5.0, 5.0], [9.0, 5.0, 5.0]]);
+}
+
+#[test]
+fn kmeans_improves_on_median_cut() {
+    // a lopsided image, where the median falls inside the big cluster
+    let mut colors = vec![[0, 0, 0]; 6];
+    colors.extend(vec![[10, 0, 0]; 6]);
+    colors.extend(vec![[250, 0, 0]; 2]);
+    let error = |palette: &[Rgb]| -> f64 {
+        colors.iter()
+            .map(|px| {
+                let rgb = px.map(|v| v as f64);
+                let p = palette[nearest(palette, rgb)];
+                (0..3).map(|c| (p[c] - rgb[c]).powi(2)).sum::<f64>()
+            })
+            .sum()
+    };
+
+    let median = PaletteMethod::MedianCut(2).palette(&colors);
+    let kmeans = PaletteMethod::KMeans(2).palette(&colors);
+    assert!(error(&kmeans) < error(&median));
+    assert_eq!(sorted(kmeans), vec![[5.0, 0.0, 0.0], [250.0, 0.0, 0.0]]);
+}
+
+#[test]
+fn remap_to_nearest() {
+    let img = image(&two_clusters());
+    let palette = img.palette(&PaletteMethod::MedianCut(2));
+    assert_eq!((palette.width, palette.height, palette.maxval), (2, 1, 255));
+
+    let params = Quantize { palette: PaletteSource::Generate(PaletteMethod::MedianCut(2)), dither: None, serpentine: false };
+    let out = img.quantize(&palette, &params);
+    assert_eq!(out.rpixels, vec![200, 200, 200, 200, 20, 20, 20, 20]);
+    assert_eq!(out.gpixels, vec![20; 8]);
+    assert_eq!(out.bpixels, vec![20, 20, 20, 20, 200, 200, 200, 200]);
+}
+
+#[test]
+fn dithered_remap_keeps_average() {
+    let gray = GrayImage { width: 16, height: 16, maxval: 255, pixels: vec![64; 256] };
+    let palette = image(&[[0, 0, 0], [255, 255, 255]]);
+    let params = Quantize {
+        palette: PaletteSource::Generate(PaletteMethod::MedianCut(2)),
+        dither: Some(Diffusion::FloydSteinberg),
+        serpentine: true,
+    };
+
+    let out = gray.quantize(&palette, &params);
+    let white = out.rpixels.iter().filter(|&&v| v == 255).count();
+    assert!(out.rpixels.iter().all(|&v| v == 0 || v == 255));
+    assert!((60..=68).contains(&white), "{} white pixels", white);
+
+    // without dithering, everything rounds to black
+    let flat = gray.quantize(&palette, &Quantize { dither: None, ..params });
+    assert!(flat.rpixels.iter().all(|&v| v == 0));
+}
+
+#[test]
+fn remap_to_palette_file() {
+    let img = Image::from(image(&[[250, 10, 240], [10, 200, 100], [30, 30, 30]]));
+    let op = ManipOption::Quantize(Quantize {
+        palette: PaletteSource::File(img_folder() + "feep.ppm"),
+        dither: None,
+        serpentine: false,
+    });
+    // feep's colors are black, magenta and green-blue, with a maxval of 15
+    match img.manipulate(&op).unwrap().0 {
+        ImageType::Color(out) => {
+            assert_eq!(out.maxval, 255);
+            assert_eq!(out.rpixels, vec![255, 0, 0]);
+            assert_eq!(out.gpixels, vec![0, 255, 0]);
+            assert_eq!(out.bpixels, vec![255, 119, 0]);
+        }
+        _ => panic!("expected a color image"),
+    }
+
+    let op = ManipOption::Quantize(Quantize {
+        palette: PaletteSource::File(img_folder() + "no_such_palette.ppm"),
+        dither: None,
+        serpentine: false,
+    });
+    assert!(img.manipulate(&op).is_err());
+}
@@ -0,0 +1,131 @@
+use super::convolve::EdgeMode;
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// The shape of the neighborhood considered by a [`RankFilter`].
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Window {
+    /// Every value within `radius` rows and columns
+    Square,
+    /// Every value within a euclidean distance of `radius`
+    Circle,
+}
+
+/// Replaces every value with a percentile of the values in its neighborhood.
+///
+/// A percentile of 50 is a median filter, 0 is a minimum (erosion) and 100 is a maximum
+/// (dilation). Neighbors which fall off an edge take the value of the nearest pixel on it.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct RankFilter {
+    pub radius: usize,
+    pub percentile: f64,
+    pub window: Window,
+}
+
+/// A histogram of pixel values which can quickly find its `k`th smallest entry.
+///
+/// Counts are kept both for every value and for blocks of 256 values, so finding a rank needs at
+/// most a few hundred steps even for 16-bit images.
+pub struct RankHistogram {
+    fine: Vec<u32>,
+    coarse: Vec<u32>,
+    count: usize,
+}
+
+impl RankHistogram {
+    pub fn new(maxval: usize) -> RankHistogram {
+        RankHistogram {
+            fine: vec![0; maxval + 1],
+            coarse: vec![0; (maxval >> 8) + 1],
+            count: 0,
+        }
+    }
+
+    pub fn add(&mut self, v: PxVal) {
+        self.fine[v as usize] += 1;
+        self.coarse[v as usize >> 8] += 1;
+        self.count += 1;
+    }
+
+    pub fn remove(&mut self, v: PxVal) {
+        self.fine[v as usize] -= 1;
+        self.coarse[v as usize >> 8] -= 1;
+        self.count -= 1;
+    }
+
+    /// Finds the `k`th smallest value (counting from zero) currently in the histogram.
+    pub fn nth(&self, k: usize) -> PxVal {
+        let mut seen = 0;
+        for (block, &n) in self.coarse.iter().enumerate() {
+            if seen + n as usize <= k {
+                seen += n as usize;
+                continue;
+            }
+            let start = block << 8;
+            let end = (start + 256).min(self.fine.len());
+            for v in start..end {
+                seen += self.fine[v] as usize;
+                if seen > k {
+                    return v as PxVal;
+                }
+            }
+        }
+        (self.fine.len() - 1) as PxVal
+    }
+
+    /// Finds the value at `percentile` (from 0 to 100) of the values in the histogram.
+    pub fn percentile(&self, percentile: f64) -> PxVal {
+        let last = self.count.saturating_sub(1) as f64;
+        self.nth((percentile / 100.0 * last).round() as usize)
+    }
+}
+
+impl RankFilter {
+    /// The half-width of the window on the row `dy` away from its center
+    fn reach(&self, dy: isize) -> isize {
+        let r = self.radius as isize;
+        match self.window {
+            Window::Square => r,
+            Window::Circle => ((r * r - dy * dy) as f64).sqrt().floor() as isize,
+        }
+    }
+
+    /// Filters a single plane of pixels.
+    ///
+    /// Each row is processed by sliding the window from left to right, so that only the values
+    /// entering and leaving the window touch the histogram.
+    pub fn apply(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        let r = self.radius as isize;
+        let reach: Vec<isize> = (-r..=r).map(|dy| self.reach(dy)).collect();
+        let at = |x: isize, y: isize| -> PxVal {
+            let x = EdgeMode::Replicate.resolve(x, width).unwrap();
+            let y = EdgeMode::Replicate.resolve(y, height).unwrap();
+            pixels[y * width + x]
+        };
+
+        let mut out = Vec::with_capacity(pixels.len());
+        for y in 0..height as isize {
+            let mut hist = RankHistogram::new(maxval);
+            for (dy, &dx) in (-r..=r).zip(reach.iter()) {
+                for sx in -dx..=dx {
+                    hist.add(at(sx, y + dy));
+                }
+            }
+
+            for x in 0..width as isize {
+                if x > 0 {
+                    for (dy, &dx) in (-r..=r).zip(reach.iter()) {
+                        hist.remove(at(x - dx - 1, y + dy));
+                        hist.add(at(x + dx, y + dy));
+                    }
+                }
+                out.push(hist.percentile(self.percentile));
+            }
+        }
+        out
+    }
+}
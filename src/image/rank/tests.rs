@@ -0,0 +1,102 @@
+////////////////////////////
+// unit tests for rank.rs //
+////////////////////////////
+use super::*;
+
+fn filter(percentile: f64, radius: usize, window: Window) -> RankFilter {
+    RankFilter { radius, percentile, window }
+}
+
+/// Finds the percentile of a whole window by sorting, for comparison
+fn brute_force(pixels: &[PxVal], width: usize, height: usize, f: &RankFilter) -> Vec<PxVal> {
+    let r = f.radius as isize;
+    let mut out = Vec::new();
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let mut window = Vec::new();
+            for dy in -r..=r {
+                for dx in -f.reach(dy)..=f.reach(dy) {
+                    let sx = (x + dx).max(0).min(width as isize - 1) as usize;
+                    let sy = (y + dy).max(0).min(height as isize - 1) as usize;
+                    window.push(pixels[sy * width + sx]);
+                }
+            }
+            window.sort_unstable();
+            let k = (f.percentile / 100.0 * (window.len() - 1) as f64).round() as usize;
+            out.push(window[k]);
+        }
+    }
+    out
+}
+
+/// Deterministic pseudo-random pixels
+fn noise(len: usize, maxval: usize) -> Vec<PxVal> {
+    let mut state: u32 = 12345;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            ((state >> 8) as usize % (maxval + 1)) as PxVal
+        })
+        .collect()
+}
+
+#[test]
+fn histogram_ranks() {
+    let mut hist = RankHistogram::new(65535);
+    for &v in &[5, 60000, 300, 300, 7] {
+        hist.add(v);
+    }
+    assert_eq!(hist.count, 5);
+    assert_eq!(hist.nth(0), 5);
+    assert_eq!(hist.nth(2), 300);
+    assert_eq!(hist.nth(3), 300);
+    assert_eq!(hist.nth(4), 60000);
+
+    hist.remove(300);
+    assert_eq!(hist.percentile(50.0), 300);
+    assert_eq!(hist.percentile(100.0), 60000);
+}
+
+#[test]
+fn median_removes_salt_and_pepper() {
+    let mut pixels = vec![100; 25];
+    pixels[6] = 255;
+    pixels[18] = 0;
+    let clean = filter(50.0, 1, Window::Square).apply(&pixels, 5, 5, 255);
+    assert_eq!(clean, vec![100; 25]);
+}
+
+#[test]
+fn min_and_max() {
+    let pixels = vec![
+        1, 2, 3,
+        4, 5, 6,
+        7, 8, 9,
+    ];
+    let eroded = filter(0.0, 1, Window::Square).apply(&pixels, 3, 3, 15);
+    assert_eq!(eroded, vec![1, 1, 2, 1, 1, 2, 4, 4, 5]);
+    let dilated = filter(100.0, 1, Window::Square).apply(&pixels, 3, 3, 15);
+    assert_eq!(dilated, vec![5, 6, 6, 8, 9, 9, 8, 9, 9]);
+}
+
+#[test]
+fn sliding_matches_brute_force() {
+    let (w, h) = (13, 9);
+    for &maxval in &[255, 65535] {
+        let pixels = noise(w * h, maxval);
+        for &window in &[Window::Square, Window::Circle] {
+            for &percentile in &[0.0, 25.0, 50.0, 100.0] {
+                let f = filter(percentile, 3, window);
+                assert_eq!(f.apply(&pixels, w, h, maxval), brute_force(&pixels, w, h, &f));
+            }
+        }
+    }
+}
+
+#[test]
+fn circle_is_narrower_than_square() {
+    let f = filter(50.0, 3, Window::Circle);
+    assert_eq!(f.reach(0), 3);
+    assert_eq!(f.reach(2), 2);
+    assert_eq!(f.reach(3), 0);
+}
@@ -0,0 +1,27 @@
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// Shrinks a plane by an integer `factor`, averaging each `factor`x`factor` block of values.
+///
+/// Blocks along the right and bottom edges may be partial, in which case only the values
+/// present are averaged. Returns the new plane along with its width and height.
+pub fn downscale(pixels: &[PxVal], width: usize, height: usize, factor: usize) -> (Vec<PxVal>, usize, usize) {
+    let factor = factor.max(1);
+    let (new_width, new_height) = (width.div_ceil(factor), height.div_ceil(factor));
+
+    let mut out = Vec::with_capacity(new_width * new_height);
+    for by in 0..new_height {
+        let rows = by * factor..((by + 1) * factor).min(height);
+        for bx in 0..new_width {
+            let cols = bx * factor..((bx + 1) * factor).min(width);
+            let sum: u64 = rows.clone()
+                .flat_map(|y| cols.clone().map(move |x| pixels[y * width + x] as u64))
+                .sum();
+            let count = (rows.len() * cols.len()) as f64;
+            out.push((sum as f64 / count).round() as PxVal);
+        }
+    }
+    (out, new_width, new_height)
+}
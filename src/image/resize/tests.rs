@@ -0,0 +1,29 @@
+//////////////////////////////
+// unit tests for resize.rs //
+//////////////////////////////
+use super::*;
+
+#[test]
+fn averages_blocks() {
+    let pixels = vec![
+        0, 2, 4, 6,
+        2, 4, 6, 8,
+    ];
+    assert_eq!(downscale(&pixels, 4, 2, 2), (vec![2, 6], 2, 1));
+}
+
+#[test]
+fn partial_blocks() {
+    let pixels = vec![
+        1, 1, 9,
+        1, 1, 9,
+        5, 5, 7,
+    ];
+    assert_eq!(downscale(&pixels, 3, 3, 2), (vec![1, 9, 5, 7], 2, 2));
+}
+
+#[test]
+fn factor_of_one_is_identity() {
+    let pixels: Vec<PxVal> = (0..6).collect();
+    assert_eq!(downscale(&pixels, 3, 2, 1), (pixels, 3, 2));
+}
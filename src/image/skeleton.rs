@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests;
+
+/// Thins the foreground of a mask down to a skeleton one pixel wide, with the Zhang-Suen
+/// algorithm.
+///
+/// Each pass looks at the eight neighbors of every foreground pixel, `P2` (above) through `P9`
+/// clockwise, and removes the pixel if it has from 2 to 6 foreground neighbors, exactly one
+/// background-to-foreground step going around them, and (alternating between passes) isn't
+/// shielded from the south-east or the north-west. Passes repeat until nothing changes. Pixels
+/// outside the image count as background.
+pub fn thin(foreground: &[bool], width: usize, height: usize) -> Vec<bool> {
+    let mut mask = foreground.to_vec();
+    let at = |mask: &[bool], x: isize, y: isize| {
+        x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height && mask[y as usize * width + x as usize]
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for step in 0..2 {
+            let mut removed = Vec::new();
+            for y in 0..height as isize {
+                for x in 0..width as isize {
+                    if !at(&mask, x, y) {
+                        continue;
+                    }
+                    // P2 to P9, clockwise from above
+                    let p = [
+                        at(&mask, x, y - 1),
+                        at(&mask, x + 1, y - 1),
+                        at(&mask, x + 1, y),
+                        at(&mask, x + 1, y + 1),
+                        at(&mask, x, y + 1),
+                        at(&mask, x - 1, y + 1),
+                        at(&mask, x - 1, y),
+                        at(&mask, x - 1, y - 1),
+                    ];
+                    let neighbors = p.iter().filter(|&&n| n).count();
+                    let transitions = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+                    let (p2, p4, p6, p8) = (p[0], p[2], p[4], p[6]);
+                    let shielded = if step == 0 {
+                        p4 && p6 && (p2 || p8)
+                    } else {
+                        p2 && p8 && (p4 || p6)
+                    };
+
+                    if (2..=6).contains(&neighbors) && transitions == 1 && !shielded {
+                        removed.push(y as usize * width + x as usize);
+                    }
+                }
+            }
+
+            changed |= !removed.is_empty();
+            for i in removed {
+                mask[i] = false;
+            }
+        }
+    }
+    mask
+}
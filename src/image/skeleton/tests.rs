@@ -0,0 +1,75 @@
+////////////////////////////////
+// unit tests for skeleton.rs //
+////////////////////////////////
+use super::*;
+use crate::image::components::{Connectivity, Labels};
+use crate::image::{ColorImage, ImageManip};
+
+fn mask(pixels: &[u8]) -> Vec<bool> {
+    pixels.iter().map(|&p| p != 0).collect()
+}
+
+#[test]
+fn thins_a_bar_to_a_line() {
+    let bar = mask(&[
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 1, 1, 1, 1, 1, 1, 1, 0,
+        0, 1, 1, 1, 1, 1, 1, 1, 0,
+        0, 1, 1, 1, 1, 1, 1, 1, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ]);
+    // as usual for Zhang-Suen, the ends of the line are worn down a little
+    let thinned = thin(&bar, 9, 5);
+    assert_eq!(thinned, mask(&[
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 1, 1, 1, 1, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ]));
+}
+
+#[test]
+fn keeps_thin_lines() {
+    let line = mask(&[
+        1, 0, 0, 0,
+        0, 1, 1, 0,
+        0, 0, 0, 1,
+    ]);
+    assert_eq!(thin(&line, 4, 3), line);
+}
+
+#[test]
+fn skeleton_stays_connected_and_inside() {
+    // a thick ring, which must keep its hole
+    let (width, height) = (15, 15);
+    let ring: Vec<bool> = (0..width * height)
+        .map(|i| {
+            let (x, y) = ((i % width) as f64 - 7.0, (i / width) as f64 - 7.0);
+            (3.0..=6.5).contains(&(x * x + y * y).sqrt())
+        })
+        .collect();
+    let thinned = thin(&ring, width, height);
+
+    assert!(thinned.iter().zip(&ring).all(|(&t, &r)| !t || r));
+    assert!(thinned.iter().filter(|&&t| t).count() < ring.iter().filter(|&&r| r).count() / 2);
+    assert_eq!(Labels::new(&thinned, width, height, Connectivity::Eight).blobs().len(), 1);
+    let holes: Vec<bool> = thinned.iter().map(|&t| !t).collect();
+    assert_eq!(Labels::new(&holes, width, height, Connectivity::Four).blobs().len(), 2);
+}
+
+#[test]
+fn skeleton_of_color_image() {
+    let img = ColorImage {
+        width: 3,
+        height: 3,
+        maxval: 255,
+        rpixels: vec![0, 9, 0, 0, 9, 0, 0, 9, 0],
+        gpixels: vec![0; 9],
+        bpixels: vec![0; 9],
+    };
+    let out = img.skeleton();
+    assert_eq!(out.maxval, 1);
+    // the skeleton comes out black on white, so it's the foreground of the bilevel result
+    assert_eq!(out.pixels, vec![1, 0, 1, 1, 0, 1, 1, 0, 1]);
+}
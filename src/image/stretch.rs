@@ -0,0 +1,53 @@
+use super::rank::RankHistogram;
+use super::PxVal;
+
+#[cfg(test)]
+mod tests;
+
+/// Parameters of a percentile-clipped contrast stretch ("auto levels").
+///
+/// The value at the `low` percentile becomes 0 and the value at the `high` percentile becomes
+/// `maxval`, with everything in between stretched linearly and everything outside clipped. Unlike
+/// a plain min/max stretch, a few outlying pixels can't stop the rest of the image from being
+/// stretched.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Stretch {
+    pub low: f64,
+    pub high: f64,
+    /// For color images, stretch every channel by the same amount (found from the luminance)
+    /// rather than each channel separately, which preserves colors
+    pub luminance: bool,
+}
+
+impl Stretch {
+    /// Finds the values at the `low` and `high` percentiles of a plane.
+    pub fn bounds(&self, pixels: &[PxVal], maxval: usize) -> (PxVal, PxVal) {
+        let mut hist = RankHistogram::new(maxval);
+        for &v in pixels {
+            hist.add(v);
+        }
+        (hist.percentile(self.low), hist.percentile(self.high))
+    }
+
+    /// Linearly maps `lo..=hi` onto `0..=maxval` for a plane, clipping values outside it.
+    ///
+    /// If `lo` and `hi` are the same there's nothing to stretch, so the plane is left alone.
+    pub fn stretch(pixels: &[PxVal], maxval: usize, (lo, hi): (PxVal, PxVal)) -> Vec<PxVal> {
+        if hi <= lo {
+            return pixels.to_vec();
+        }
+        let range = (hi - lo) as f64;
+        pixels.iter()
+            .map(|&v| {
+                let v = (v.max(lo) - lo) as f64 * maxval as f64 / range;
+                v.round().min(maxval as f64) as PxVal
+            })
+            .collect()
+    }
+
+    /// Stretches a single plane between its own percentiles.
+    pub fn apply(&self, pixels: &[PxVal], maxval: usize) -> Vec<PxVal> {
+        Stretch::stretch(pixels, maxval, self.bounds(pixels, maxval))
+    }
+}
@@ -0,0 +1,65 @@
+///////////////////////////////
+// unit tests for stretch.rs //
+///////////////////////////////
+use super::*;
+use crate::image::{ColorImage, ImageManip};
+
+fn stretch(low: f64, high: f64, luminance: bool) -> Stretch {
+    Stretch { low, high, luminance }
+}
+
+#[test]
+fn full_range_is_min_max_stretch() {
+    let pixels = vec![50, 100, 150];
+    assert_eq!(stretch(0.0, 100.0, false).apply(&pixels, 255), vec![0, 128, 255]);
+}
+
+#[test]
+fn percentiles_ignore_outliers() {
+    // a min/max stretch can't do anything with a single 0 and a single 255
+    let mut pixels: Vec<PxVal> = (0..98).map(|i| 100 + (i % 2) * 20).collect();
+    pixels.push(0);
+    pixels.push(255);
+    assert_eq!(stretch(0.0, 100.0, false).apply(&pixels, 255), pixels);
+
+    let out = stretch(2.0, 98.0, false).apply(&pixels, 255);
+    assert_eq!(out[0], 0);
+    assert_eq!(out[1], 255);
+    assert_eq!(out[98], 0);
+    assert_eq!(out[99], 255);
+}
+
+#[test]
+fn flat_plane_unchanged() {
+    let pixels = vec![7; 9];
+    assert_eq!(stretch(1.0, 99.0, false).apply(&pixels, 15), pixels);
+}
+
+#[test]
+fn sixteen_bit() {
+    let pixels = vec![1000, 2000, 3000];
+    assert_eq!(stretch(0.0, 100.0, false).apply(&pixels, 65535), vec![0, 32768, 65535]);
+}
+
+#[test]
+fn color_stays_color() {
+    let img = ColorImage {
+        width: 3,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![60, 80, 100],
+        gpixels: vec![50, 70, 90],
+        bpixels: vec![40, 60, 80],
+    };
+
+    // per channel, every channel spans the full range (which shifts the colors)
+    let out = img.stretch(&stretch(0.0, 100.0, false));
+    assert_eq!(out.rpixels, vec![0, 128, 255]);
+    assert_eq!(out.bpixels, vec![0, 128, 255]);
+
+    // with a shared stretch, red stays redder than blue
+    let out = img.stretch(&stretch(0.0, 100.0, true));
+    assert!(out.rpixels[1] > out.gpixels[1]);
+    assert!(out.gpixels[1] > out.bpixels[1]);
+    assert_eq!(out.rpixels[1] - out.gpixels[1], out.gpixels[1] - out.bpixels[1]);
+}
@@ -0,0 +1,209 @@
+use super::blur::gaussian_plane;
+use super::convolve::{EdgeMode, Kernel};
+use super::{plane_from_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// Parameters of an emboss, which shades the image as though its brightness were the height of
+/// a surface lit from one side.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Emboss {
+    /// The direction the light comes from, in degrees counterclockwise from the right
+    pub angle: f64,
+    /// How strongly slopes are shaded
+    pub depth: f64,
+}
+
+impl Emboss {
+    /// Builds the 3x3 kernel of the emboss, which weighs each neighbor by how far it lies away
+    /// from the light, and adds mid-gray so that flat areas come out gray.
+    pub fn kernel(&self, maxval: usize) -> Kernel {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let weights = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx as f64, dy as f64)))
+            // image rows run downwards, so the light's vertical component is flipped
+            .map(|(dx, dy)| -self.depth * (dx * cos - dy * sin))
+            .collect();
+        Kernel::new(3, 3, weights)
+            .unwrap()
+            .with_divisor(1.0)
+            .with_bias(maxval as f64 / 2.0)
+            .with_edge(EdgeMode::Center)
+    }
+
+    /// Embosses a plane of intensities.
+    pub fn apply(&self, plane: &[f64], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        plane_from_f64(&self.kernel(maxval).convolve(plane, width, height), maxval)
+    }
+}
+
+/// A painterly, edge-preserving smoothing filter.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Paint {
+    /// The Kuwahara filter: of the four `(radius + 1)`-wide squares which have a pixel as a
+    /// corner, take the mean of the one with the least variance
+    Kuwahara(usize),
+    /// Sort the pixels within `radius` into `levels` bins of intensity, and take the mean of the
+    /// fullest bin
+    Oil { radius: usize, levels: usize },
+}
+
+impl Paint {
+    /// Filters several planes of the same image together.
+    ///
+    /// Which pixels are averaged is decided from `intensity` (e.g. the luminance of a color
+    /// image), so every plane averages the same pixels and colors don't shift. As for
+    /// sharpen/smooth, neighbors which fall off an edge take the value of the center pixel.
+    pub fn apply(
+        &self,
+        planes: &[&[PxVal]],
+        intensity: &[f64],
+        width: usize,
+        height: usize,
+        maxval: usize,
+    ) -> Vec<Vec<PxVal>> {
+        match *self {
+            Paint::Kuwahara(radius) => kuwahara(planes, intensity, width, height, radius),
+            Paint::Oil { radius, levels } => oil(planes, intensity, width, height, maxval, radius, levels),
+        }
+    }
+}
+
+/// Sums over rectangles of a plane, in O(1) each
+struct SummedArea {
+    /// Width of the table, one more than the plane's
+    stride: usize,
+    sums: Vec<f64>,
+}
+
+impl SummedArea {
+    fn new<F>(width: usize, height: usize, value: F) -> SummedArea
+    where
+        F: Fn(usize) -> f64
+    {
+        let stride = width + 1;
+        let mut sums = vec![0.0; stride * (height + 1)];
+        for y in 0..height {
+            let mut row = 0.0;
+            for x in 0..width {
+                row += value(y * width + x);
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+            }
+        }
+        SummedArea { stride, sums }
+    }
+
+    /// The sum over columns `x0..x1` and rows `y0..y1`
+    fn sum(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> f64 {
+        let s = self.stride;
+        self.sums[y1 * s + x1] - self.sums[y0 * s + x1] - self.sums[y1 * s + x0] + self.sums[y0 * s + x0]
+    }
+
+    /// The sum over the `size`x`size` square with its top left corner at `(x0, y0)`, where any
+    /// part of the square which falls off the image takes the value `center`
+    fn square(&self, x0: isize, y0: isize, size: usize, center: f64) -> f64 {
+        let (width, height) = (self.stride - 1, self.sums.len() / self.stride - 1);
+        let clamp = |v: isize, len: usize| v.clamp(0, len as isize) as usize;
+        let (x1, y1) = (clamp(x0 + size as isize, width), clamp(y0 + size as isize, height));
+        let (x0, y0) = (clamp(x0, width), clamp(y0, height));
+        let inside = (x1 - x0) * (y1 - y0);
+        self.sum(x0, y0, x1, y1) + (size * size - inside) as f64 * center
+    }
+}
+
+fn kuwahara(planes: &[&[PxVal]], intensity: &[f64], width: usize, height: usize, r: usize) -> Vec<Vec<PxVal>> {
+    let sums = SummedArea::new(width, height, |i| intensity[i]);
+    let squares = SummedArea::new(width, height, |i| intensity[i] * intensity[i]);
+    let plane_sums: Vec<SummedArea> = planes.iter()
+        .map(|p| SummedArea::new(width, height, |i| p[i] as f64))
+        .collect();
+    let size = r + 1;
+    let count = (size * size) as f64;
+
+    let mut out = vec![Vec::with_capacity(width * height); planes.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let (x, y, r) = (x as isize, y as isize, r as isize);
+            let (qx, qy) = [(x - r, y - r), (x, y - r), (x - r, y), (x, y)]
+                .iter()
+                .map(|&(qx, qy)| {
+                    let mean = sums.square(qx, qy, size, intensity[i]) / count;
+                    let variance = squares.square(qx, qy, size, intensity[i] * intensity[i]) / count - mean * mean;
+                    ((qx, qy), variance)
+                })
+                .fold(((0, 0), f64::INFINITY), |best, q| if q.1 < best.1 { q } else { best })
+                .0;
+            for ((o, s), p) in out.iter_mut().zip(plane_sums.iter()).zip(planes.iter()) {
+                o.push((s.square(qx, qy, size, p[i] as f64) / count).round() as PxVal);
+            }
+        }
+    }
+    out
+}
+
+fn oil(
+    planes: &[&[PxVal]],
+    intensity: &[f64],
+    width: usize,
+    height: usize,
+    maxval: usize,
+    radius: usize,
+    levels: usize,
+) -> Vec<Vec<PxVal>> {
+    let levels = levels.max(1);
+    let bins: Vec<usize> = intensity.iter()
+        .map(|&v| ((v / maxval as f64 * levels as f64) as usize).min(levels - 1))
+        .collect();
+    let r = radius as isize;
+
+    let mut out = vec![Vec::with_capacity(width * height); planes.len()];
+    let mut counts = vec![0; levels];
+    let mut sums = vec![vec![0.0; levels]; planes.len()];
+    for y in 0..height {
+        for x in 0..width {
+            counts.iter_mut().for_each(|c| *c = 0);
+            sums.iter_mut().for_each(|s| s.iter_mut().for_each(|v| *v = 0.0));
+            for dy in -r..=r {
+                let sy = EdgeMode::Center.resolve(y as isize + dy, height);
+                for dx in -r..=r {
+                    let sx = EdgeMode::Center.resolve(x as isize + dx, width);
+                    let i = match (sy, sx) {
+                        (Some(sy), Some(sx)) => sy * width + sx,
+                        _ => y * width + x,
+                    };
+                    counts[bins[i]] += 1;
+                    for (s, p) in sums.iter_mut().zip(planes.iter()) {
+                        s[bins[i]] += p[i] as f64;
+                    }
+                }
+            }
+
+            // the fullest bin, preferring the darker of any ties
+            let fullest = (0..levels).fold(0, |best, b| if counts[b] > counts[best] { b } else { best });
+            for (o, s) in out.iter_mut().zip(sums.iter()) {
+                o.push((s[fullest] / counts[fullest] as f64).round() as PxVal);
+            }
+        }
+    }
+    out
+}
+
+/// Draws a plane of intensities as a pencil sketch.
+///
+/// The plane is inverted, blurred with a gaussian of standard deviation `sigma`, and then
+/// "color dodged" onto the original: `value * maxval / (maxval - blurred)`. Flat areas cancel out
+/// to white, leaving dark lines wherever the intensity changes.
+pub fn sketch(plane: &[f64], width: usize, height: usize, maxval: usize, sigma: f64) -> Vec<PxVal> {
+    let max = maxval as f64;
+    let inverted: Vec<f64> = plane.iter().map(|v| max - v).collect();
+    let blurred = gaussian_plane(&inverted, width, height, sigma, false);
+    let dodged: Vec<f64> = plane.iter()
+        .zip(blurred.iter())
+        .map(|(v, b)| if *b >= max { max } else { v * max / (max - b) })
+        .collect();
+    plane_from_f64(&dodged, maxval)
+}
@@ -0,0 +1,100 @@
+///////////////////////////////
+// unit tests for stylize.rs //
+///////////////////////////////
+use super::*;
+use crate::image::{plane_to_f64, ColorImage, GrayImage, ImageManip};
+
+/// A dark left half and a bright right half
+fn step(width: usize, height: usize) -> Vec<PxVal> {
+    (0..width * height).map(|i| if i % width < width / 2 { 50 } else { 200 }).collect()
+}
+
+#[test]
+fn emboss_shades_slopes() {
+    let plane = plane_to_f64(&step(6, 3));
+    // light from the right: the step up faces away from the light, so it's shaded dark
+    let out = Emboss { angle: 0.0, depth: 1.0 }.apply(&plane, 6, 3, 255);
+    assert_eq!(&out[..6], &[128, 128, 0, 0, 128, 128]);
+
+    // light from the left lights it up instead, and the edges (off which neighbors take the
+    // center value) stay gray
+    let out = Emboss { angle: 180.0, depth: 0.5 }.apply(&plane, 6, 3, 255);
+    assert_eq!(&out[..6], &[128, 128, 255, 255, 128, 128]);
+}
+
+#[test]
+fn kuwahara_preserves_edges() {
+    // a noisy step: smoothing should flatten the noise but keep the step where it is
+    let mut pixels = step(8, 8);
+    pixels[9] = 70;
+    pixels[13] = 180;
+    let out = Paint::Kuwahara(2).apply(&[&pixels], &plane_to_f64(&pixels), 8, 8, 255).remove(0);
+    for (got, want) in out.iter().zip(step(8, 8)) {
+        assert!((*got as i32 - want as i32).abs() <= 3, "{} vs {}", got, want);
+    }
+}
+
+#[test]
+fn paint_edges_take_center_value() {
+    // off the edge, every neighbor is a copy of the pixel itself, so a lone bright corner is
+    // kept rather than being averaged away with its replicated row
+    let pixels = vec![
+        250, 10, 10,
+        10, 10, 10,
+    ];
+    let intensity = plane_to_f64(&pixels);
+    let out = Paint::Kuwahara(1).apply(&[&pixels], &intensity, 3, 2, 255).remove(0);
+    assert_eq!(out[0], 250);
+    let out = Paint::Oil { radius: 1, levels: 4 }.apply(&[&pixels], &intensity, 3, 2, 255).remove(0);
+    assert_eq!(out[0], 250);
+}
+
+#[test]
+fn kuwahara_of_flat_plane() {
+    let pixels = vec![77; 20];
+    let out = Paint::Kuwahara(3).apply(&[&pixels], &plane_to_f64(&pixels), 5, 4, 255).remove(0);
+    assert_eq!(out, pixels);
+}
+
+#[test]
+fn oil_takes_most_common_intensity() {
+    let pixels = vec![
+        10, 12, 11,
+        240, 14, 13,
+        250, 12, 11,
+    ];
+    let out = Paint::Oil { radius: 1, levels: 4 }.apply(&[&pixels], &plane_to_f64(&pixels), 3, 3, 255);
+    // the center's neighborhood holds 7 dark pixels, averaging 12
+    assert_eq!(out[0][4], 12);
+    // the bottom left corner's neighborhood is mostly bright, counting the five neighbors off
+    // the edge as copies of the corner itself
+    assert_eq!(out[0][6], 249);
+}
+
+#[test]
+fn paint_shares_decisions_across_channels() {
+    let color = ColorImage {
+        width: 3,
+        height: 3,
+        maxval: 255,
+        rpixels: vec![200, 200, 200, 200, 10, 200, 200, 200, 200],
+        gpixels: vec![10, 10, 10, 10, 200, 10, 10, 10, 10],
+        bpixels: vec![10; 9],
+    };
+    let out = color.paint(&Paint::Oil { radius: 1, levels: 8 });
+    // red's luminance is the more common, so only red pixels are averaged
+    assert_eq!((out.rpixels[4], out.gpixels[4], out.bpixels[4]), (200, 10, 10));
+}
+
+#[test]
+fn sketch_draws_lines_at_edges() {
+    let plane = plane_to_f64(&step(16, 4));
+    let out = sketch(&plane, 16, 4, 255, 2.0);
+    // flat areas dodge to white (or near it), while the dark side of the step is darkened
+    assert_eq!(out[0], 255);
+    assert_eq!(out[15], 255);
+    assert!(out[7] < 150);
+
+    let gray = GrayImage { width: 16, height: 4, maxval: 255, pixels: step(16, 4) };
+    assert_eq!(gray.sketch(2.0).pixels, out);
+}
@@ -7,7 +7,8 @@ use super::*;
 // Helper functions for tests //
 ////////////////////////////////
 
-fn img_folder() -> String {
+/// The folder of sample images, shared with the tests of every submodule
+pub(super) fn img_folder() -> String {
     env!("CARGO_MANIFEST_DIR").to_owned() + "/img/"
 }
 
@@ -143,3 +144,66 @@ fn raw_and_ascii_ppms_equal() {
     assert_eq!(raw_img, ascii_img);
 }
 
+
+#[test]
+fn reject_values_above_maxval() {
+    for data in [&b"P2\n2 1\n3\n1 4\n"[..], b"P5\n2 1\n3\n\x01\x04", b"P3\n1 1\n3\n1 2 9\n"] {
+        let hdr = ImageHeader::read(data).unwrap();
+        let loaded = if hdr.is_color {
+            ColorImage::try_from(hdr).map(Image::from)
+        } else {
+            GrayImage::try_from(hdr).map(Image::from)
+        };
+        assert_eq!(loaded.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn open_bitmaps() {
+    let expected = Image(ImageType::Grayscale(GrayImage {
+        width: 10,
+        height: 3,
+        maxval: 1,
+        pixels: vec![
+            0, 1, 1, 1, 1, 1, 1, 1, 1, 0,
+            1, 0, 0, 1, 1, 0, 0, 1, 0, 1,
+            0, 0, 0, 0, 0, 1, 1, 1, 1, 1,
+        ],
+    }));
+    for name in &["bits.pbm", "bits_packed.pbm", "bits_raw.pbm"] {
+        assert_eq!(Image::load(&(img_folder() + name)).unwrap(), expected);
+    }
+}
+
+#[test]
+fn bitmaps_round_trip() {
+    let img = Image::load(&(img_folder() + "bits_raw.pbm")).unwrap();
+    let raw = std::fs::read(img_folder() + "bits_raw.pbm").unwrap();
+    assert_eq!(img.encode_bitmap(&OutputMode::BinaryBitmap).unwrap(), raw);
+}
+
+#[test]
+fn brighten_clamps() {
+    let img = make_gray_image();
+    assert_eq!(img.brighten(5).pixels, vec![6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 15, 15]);
+    assert_eq!(img.brighten(-3).pixels, vec![0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+}
+
+#[test]
+fn negate_subtracts_from_maxval() {
+    let img = make_color_image().negate();
+    assert_eq!(img.rpixels[0], 254);
+    assert_eq!(img.bpixels[11], 241);
+    assert_eq!(make_gray_image().negate().pixels[0], 14);
+}
+
+#[test]
+fn contrast_stretches_to_maxval() {
+    let img = make_gray_image().contrast();
+    assert_eq!(img.pixels[0], 0);
+    assert_eq!(img.pixels[11], 15);
+    assert_eq!(img.pixels[5], 7); // 15 * 5 / 11 = 6.8
+
+    let flat = GrayImage { pixels: vec![4; 12], ..*make_gray_image() };
+    assert_eq!(flat.contrast(), flat);
+}
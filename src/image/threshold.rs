@@ -0,0 +1,92 @@
+use super::blur::box_blur;
+use super::histogram::counts;
+use super::{plane_to_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// A way of choosing the threshold which splits a plane into black and white.
+///
+/// Values above the threshold become 1 (white) and everything else becomes 0 (black).
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Threshold {
+    /// A single threshold, as a fraction of `maxval`
+    Fixed(f64),
+    /// A single threshold which best separates the histogram into two classes (Otsu's method)
+    Otsu,
+    /// The mean of the `(2*radius + 1)`-wide square around each pixel, less `offset` (a
+    /// fraction of `maxval`)
+    Mean { radius: usize, offset: f64 },
+    /// Sauvola's threshold `mean * (1 + k * (stddev / R - 1))` over the `(2*radius + 1)`-wide
+    /// square around each pixel, where `R` is half of `maxval`
+    Sauvola { radius: usize, k: f64 },
+}
+
+impl Threshold {
+    /// Binarizes a plane, returning a bilevel plane of 0s and 1s.
+    pub fn apply(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        let max = maxval as f64;
+        match *self {
+            Threshold::Fixed(t) => binarize(pixels, |_| t * max),
+            Threshold::Otsu => {
+                let t = otsu(&counts(pixels, maxval)) as f64;
+                binarize(pixels, |_| t)
+            }
+            Threshold::Mean { radius, offset } => {
+                let mean = box_blur(&plane_to_f64(pixels), width, height, radius);
+                binarize(pixels, |i| mean[i] - offset * max)
+            }
+            Threshold::Sauvola { radius, k } => {
+                let plane = plane_to_f64(pixels);
+                let squares: Vec<f64> = plane.iter().map(|v| v * v).collect();
+                let mean = box_blur(&plane, width, height, radius);
+                let mean_sq = box_blur(&squares, width, height, radius);
+                binarize(pixels, |i| {
+                    let stddev = (mean_sq[i] - mean[i] * mean[i]).max(0.0).sqrt();
+                    mean[i] * (1.0 + k * (stddev / (max / 2.0) - 1.0))
+                })
+            }
+        }
+    }
+}
+
+/// Sets each pixel to 1 if it's above `threshold(index)`, or 0 otherwise
+fn binarize<F>(pixels: &[PxVal], threshold: F) -> Vec<PxVal>
+where
+    F: Fn(usize) -> f64
+{
+    pixels.iter()
+        .enumerate()
+        .map(|(i, &v)| (v as f64 > threshold(i)) as PxVal)
+        .collect()
+}
+
+/// Finds the threshold which maximizes the variance between the values at or below it and the
+/// values above it, given a histogram with a bin for every value.
+///
+/// If every value is the same there's nothing to separate, and that value is returned.
+pub fn otsu(hist: &[usize]) -> usize {
+    let total: usize = hist.iter().sum();
+    let sum: f64 = hist.iter().enumerate().map(|(v, &n)| (v * n) as f64).sum();
+
+    let (mut best, mut best_variance) = (hist.iter().position(|&n| n > 0).unwrap_or(0), 0.0);
+    let (mut below, mut below_sum) = (0, 0.0);
+    for (t, &n) in hist.iter().enumerate() {
+        below += n;
+        below_sum += (t * n) as f64;
+        let above = total - below;
+        if below == 0 || above == 0 {
+            continue;
+        }
+
+        let mean_below = below_sum / below as f64;
+        let mean_above = (sum - below_sum) / above as f64;
+        let variance = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+        if variance > best_variance {
+            best = t;
+            best_variance = variance;
+        }
+    }
+    best
+}
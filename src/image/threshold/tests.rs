@@ -0,0 +1,82 @@
+/////////////////////////////////
+// unit tests for threshold.rs //
+/////////////////////////////////
+use super::*;
+use crate::image::{ColorImage, GrayImage, ImageManip};
+
+#[test]
+fn fixed_threshold() {
+    let pixels = [0, 100, 127, 128, 255];
+    assert_eq!(Threshold::Fixed(0.5).apply(&pixels, 5, 1, 255), vec![0, 0, 0, 1, 1]);
+    assert_eq!(Threshold::Fixed(0.0).apply(&pixels, 5, 1, 255), vec![0, 1, 1, 1, 1]);
+    assert_eq!(Threshold::Fixed(1.0).apply(&pixels, 5, 1, 255), vec![0; 5]);
+}
+
+#[test]
+fn otsu_separates_two_peaks() {
+    let mut hist = vec![0; 16];
+    hist[2] = 10;
+    hist[3] = 20;
+    hist[11] = 15;
+    hist[12] = 5;
+    let t = otsu(&hist);
+    assert!((3..11).contains(&t));
+
+    let pixels = [2, 3, 3, 11, 12, 2];
+    assert_eq!(Threshold::Otsu.apply(&pixels, 6, 1, 15), vec![0, 0, 0, 1, 1, 0]);
+}
+
+#[test]
+fn otsu_of_flat_image() {
+    let mut hist = vec![0; 16];
+    hist[7] = 4;
+    assert_eq!(otsu(&hist), 7);
+    assert_eq!(Threshold::Otsu.apply(&[7; 4], 2, 2, 15), vec![0; 4]);
+}
+
+/// Dark text (40 below its surroundings) on a background that brightens from left to right,
+/// so that the text on the right is brighter than the background on the left
+fn uneven_page() -> Vec<PxVal> {
+    let (width, height) = (16, 8);
+    (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+            let background = 60 + 6 * x as PxVal;
+            if y == 4 && x % 4 == 1 { background - 40 } else { background }
+        })
+        .collect()
+}
+
+#[test]
+fn adaptive_thresholds_follow_background() {
+    let page = uneven_page();
+    let text: Vec<PxVal> = (0..page.len()).map(|i| !(i / 16 == 4 && i % 4 == 1) as PxVal).collect();
+
+    // no single threshold can pick out the text on both sides of the page
+    let global = Threshold::Otsu.apply(&page, 16, 8, 255);
+    assert_ne!(global, text);
+
+    let mean = Threshold::Mean { radius: 2, offset: 0.03 }.apply(&page, 16, 8, 255);
+    assert_eq!(mean, text);
+    let sauvola = Threshold::Sauvola { radius: 2, k: 0.1 }.apply(&page, 16, 8, 255);
+    assert_eq!(sauvola, text);
+}
+
+#[test]
+fn bilevel_results() {
+    let gray = GrayImage { width: 2, height: 1, maxval: 15, pixels: vec![3, 12] };
+    let out = gray.threshold(&Threshold::Otsu);
+    assert_eq!((out.maxval, out.pixels), (1, vec![0, 1]));
+
+    // color images are thresholded by their luminance
+    let color = ColorImage {
+        width: 2,
+        height: 1,
+        maxval: 255,
+        rpixels: vec![255, 0],
+        gpixels: vec![0, 255],
+        bpixels: vec![0, 0],
+    };
+    let out = color.threshold(&Threshold::Fixed(0.5));
+    assert_eq!((out.maxval, out.pixels), (1, vec![0, 1]));
+}
@@ -0,0 +1,151 @@
+use super::{apply_lut, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// A tonal adjustment, mapping each value to a new one independently of its neighbors.
+///
+/// All parameters are fractions of `maxval` (from 0 to 1), so the same adjustment works for any
+/// bit depth.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ToneCurve {
+    /// `new = value^(1/gamma)`, so a gamma above 1 brightens midtones
+    Gamma(f64),
+    /// Map the input range `black..white` onto the output range `out_black..out_white`, with a
+    /// gamma correction in between
+    Levels {
+        black: f64,
+        white: f64,
+        gamma: f64,
+        out_black: f64,
+        out_white: f64,
+    },
+    /// Interpolate between `(input, output)` control points, either with straight lines or with
+    /// a smooth (monotone cubic) spline
+    Curve {
+        points: Vec<(f64, f64)>,
+        /// The spline's tangent at each point, or `None` for straight lines
+        tangents: Option<Vec<f64>>,
+    },
+}
+
+impl ToneCurve {
+    /// Builds a curve through `points` (sorted by input), working out a spline's tangents once
+    /// up front rather than for every value it's evaluated at.
+    pub fn curve(points: Vec<(f64, f64)>, spline: bool) -> ToneCurve {
+        let tangents = if spline { Some(spline_tangents(&points)) } else { None };
+        ToneCurve::Curve { points, tangents }
+    }
+
+    /// Evaluates the adjustment for a single value `x` from 0 to 1.
+    pub fn eval(&self, x: f64) -> f64 {
+        match self {
+            ToneCurve::Gamma(gamma) => x.powf(1.0 / gamma),
+            ToneCurve::Levels { black, white, gamma, out_black, out_white } => {
+                let t = ((x - black) / (white - black)).clamp(0.0, 1.0);
+                out_black + t.powf(1.0 / gamma) * (out_white - out_black)
+            }
+            ToneCurve::Curve { points, tangents } => match tangents {
+                Some(tangents) => monotone_spline(points, tangents, x),
+                None => piecewise_linear(points, x),
+            },
+        }
+    }
+
+    /// Builds a lookup table holding the adjusted value of every value from 0 to `maxval`.
+    pub fn lut(&self, maxval: usize) -> Vec<PxVal> {
+        let max = maxval as f64;
+        (0..=maxval)
+            .map(|v| (self.eval(v as f64 / max) * max).round().max(0.0).min(max) as PxVal)
+            .collect()
+    }
+
+    /// Adjusts a single plane of pixels.
+    pub fn apply(&self, pixels: &[PxVal], maxval: usize) -> Vec<PxVal> {
+        apply_lut(pixels, &self.lut(maxval))
+    }
+}
+
+/// Finds the segment of `points` (sorted by input) containing `x`.
+///
+/// Returns `Err` with the output value when `x` is outside every segment, which is the output
+/// of the nearest end point.
+fn segment(points: &[(f64, f64)], x: f64) -> Result<usize, f64> {
+    match points {
+        [] => Err(x),
+        [(_, y)] => Err(*y),
+        _ if x <= points[0].0 => Err(points[0].1),
+        _ if x >= points[points.len() - 1].0 => Err(points[points.len() - 1].1),
+        _ => Ok(points.windows(2).position(|w| x < w[1].0).unwrap()),
+    }
+}
+
+fn piecewise_linear(points: &[(f64, f64)], x: f64) -> f64 {
+    match segment(points, x) {
+        Err(y) => y,
+        Ok(i) => {
+            let ((x0, y0), (x1, y1)) = (points[i], points[i + 1]);
+            y0 + (x - x0) / (x1 - x0) * (y1 - y0)
+        }
+    }
+}
+
+/// Finds the tangents of a monotone cubic Hermite spline (Fritsch-Carlson) through `points`.
+///
+/// Unlike an ordinary cubic spline, this never overshoots the control points, so a curve through
+/// increasing points is itself increasing and can't create banding by reversing tones.
+fn spline_tangents(points: &[(f64, f64)]) -> Vec<f64> {
+    let n = points.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+    let slopes: Vec<f64> = points.windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+        .collect();
+
+    // tangents at each point: averages of the neighboring slopes, flattened at local extrema
+    let mut tangents = vec![0.0; n];
+    tangents[0] = slopes[0];
+    tangents[n - 1] = slopes[n - 2];
+    for k in 1..n - 1 {
+        tangents[k] = if slopes[k - 1] * slopes[k] <= 0.0 {
+            0.0
+        } else {
+            (slopes[k - 1] + slopes[k]) / 2.0
+        };
+    }
+
+    // limit the tangents so that each segment stays monotone
+    for k in 0..n - 1 {
+        if slopes[k] == 0.0 {
+            tangents[k] = 0.0;
+            tangents[k + 1] = 0.0;
+            continue;
+        }
+        let a = tangents[k] / slopes[k];
+        let b = tangents[k + 1] / slopes[k];
+        let h = a.hypot(b);
+        if h > 3.0 {
+            tangents[k] = 3.0 * a / h * slopes[k];
+            tangents[k + 1] = 3.0 * b / h * slopes[k];
+        }
+    }
+    tangents
+}
+
+/// Interpolates with a monotone cubic Hermite spline, given its `tangents` at every point.
+fn monotone_spline(points: &[(f64, f64)], tangents: &[f64], x: f64) -> f64 {
+    let i = match segment(points, x) {
+        Err(y) => return y,
+        Ok(i) => i,
+    };
+
+    let ((x0, y0), (x1, y1)) = (points[i], points[i + 1]);
+    let dx = x1 - x0;
+    let t = (x - x0) / dx;
+    let (t2, t3) = (t * t, t * t * t);
+    (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+        + (t3 - 2.0 * t2 + t) * dx * tangents[i]
+        + (-2.0 * t3 + 3.0 * t2) * y1
+        + (t3 - t2) * dx * tangents[i + 1]
+}
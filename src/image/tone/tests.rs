@@ -0,0 +1,70 @@
+////////////////////////////
+// unit tests for tone.rs //
+////////////////////////////
+use super::*;
+
+fn identity() -> ToneCurve {
+    ToneCurve::curve(vec![(0.0, 0.0), (1.0, 1.0)], false)
+}
+
+#[test]
+fn lut_sized_to_maxval() {
+    assert_eq!(identity().lut(15).len(), 16);
+    assert_eq!(identity().lut(65535).len(), 65536);
+    assert_eq!(identity().lut(255), (0..=255).collect::<Vec<PxVal>>());
+}
+
+#[test]
+fn gamma() {
+    let lut = ToneCurve::Gamma(2.0).lut(100);
+    assert_eq!(lut[0], 0);
+    assert_eq!(lut[25], 50);
+    assert_eq!(lut[100], 100);
+    assert_eq!(ToneCurve::Gamma(1.0).lut(255), identity().lut(255));
+}
+
+#[test]
+fn levels() {
+    let levels = ToneCurve::Levels { black: 0.2, white: 0.6, gamma: 1.0, out_black: 0.0, out_white: 1.0 };
+    let lut = levels.lut(100);
+    assert_eq!(lut[10], 0);
+    assert_eq!(lut[20], 0);
+    assert_eq!(lut[40], 50);
+    assert_eq!(lut[60], 100);
+    assert_eq!(lut[90], 100);
+
+    let levels = ToneCurve::Levels { black: 0.0, white: 1.0, gamma: 1.0, out_black: 0.1, out_white: 0.5 };
+    assert_eq!(levels.apply(&[0, 50, 100], 100), vec![10, 30, 50]);
+}
+
+#[test]
+fn piecewise_curve() {
+    let curve = ToneCurve::curve(vec![(0.2, 0.0), (0.5, 0.9), (1.0, 1.0)], false);
+    assert_eq!(curve.apply(&[0, 20, 35, 50, 75, 100], 100), vec![0, 0, 45, 90, 95, 100]);
+}
+
+#[test]
+fn spline_passes_through_points() {
+    let points = vec![(0.0, 0.0), (0.25, 0.4), (0.5, 0.6), (1.0, 1.0)];
+    let curve = ToneCurve::curve(points.clone(), true);
+    for (x, y) in points {
+        assert!((curve.eval(x) - y).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn spline_is_monotone() {
+    // a steep s-curve, where an ordinary cubic spline would overshoot
+    let curve = ToneCurve::curve(vec![(0.0, 0.0), (0.4, 0.05), (0.6, 0.95), (1.0, 1.0)], true);
+    let lut = curve.lut(1023);
+    for w in lut.windows(2) {
+        assert!(w[0] <= w[1]);
+    }
+    assert_eq!(lut[1023], 1023);
+}
+
+#[test]
+fn sixteen_bit() {
+    let lut = ToneCurve::Gamma(0.5).lut(65535);
+    assert_eq!(lut[32768], 16384);
+}
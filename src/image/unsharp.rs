@@ -0,0 +1,47 @@
+use super::blur::gaussian_plane;
+use super::{plane_from_f64, plane_to_f64, PxVal};
+
+#[cfg(test)]
+mod tests;
+
+/// Parameters of an unsharp mask.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Unsharp {
+    /// Standard deviation of the gaussian used to find the blurred (unsharp) image
+    pub radius: f64,
+    /// How much of the difference between the image and its blur is added back
+    pub amount: f64,
+    /// Differences smaller than this (in sample values) are left alone, so noise isn't amplified
+    pub threshold: f64,
+    /// For color images, sharpen only the luminance so that colors don't fringe
+    pub luminance: bool,
+}
+
+impl Unsharp {
+    /// Finds the detail which an unsharp mask adds to a floating point plane.
+    ///
+    /// This is the difference between the plane and its gaussian blur, scaled by `amount`, and
+    /// zeroed wherever it falls below `threshold`.
+    pub fn detail(&self, plane: &[f64], width: usize, height: usize) -> Vec<f64> {
+        let blurred = gaussian_plane(plane, width, height, self.radius, false);
+        plane.iter()
+            .zip(blurred.iter())
+            .map(|(v, b)| {
+                let diff = v - b;
+                if diff.abs() < self.threshold { 0.0 } else { self.amount * diff }
+            })
+            .collect()
+    }
+
+    /// Sharpens a single plane of pixels.
+    pub fn apply(&self, pixels: &[PxVal], width: usize, height: usize, maxval: usize) -> Vec<PxVal> {
+        let plane = plane_to_f64(pixels);
+        let sharpened: Vec<f64> = self.detail(&plane, width, height)
+            .iter()
+            .zip(plane.iter())
+            .map(|(d, v)| v + d)
+            .collect();
+        plane_from_f64(&sharpened, maxval)
+    }
+}
@@ -0,0 +1,55 @@
+///////////////////////////////
+// unit tests for unsharp.rs //
+///////////////////////////////
+use super::*;
+use crate::image::{ColorImage, ImageManip};
+
+fn params(threshold: f64, luminance: bool) -> Unsharp {
+    Unsharp { radius: 1.0, amount: 1.0, threshold, luminance }
+}
+
+/// A 8x1 step from dark to light
+fn step() -> Vec<PxVal> {
+    vec![50, 50, 50, 50, 150, 150, 150, 150]
+}
+
+#[test]
+fn constant_plane_unchanged() {
+    let pixels = vec![100; 16];
+    assert_eq!(params(0.0, false).apply(&pixels, 4, 4, 255), pixels);
+}
+
+#[test]
+fn step_overshoots() {
+    let sharp = params(0.0, false).apply(&step(), 8, 1, 255);
+    assert!(sharp[3] < 50);
+    assert!(sharp[4] > 150);
+    assert_eq!(sharp[0], 50);
+    assert_eq!(sharp[7], 150);
+}
+
+#[test]
+fn threshold_ignores_small_differences() {
+    let noisy = vec![100, 102, 99, 101, 100, 98, 101, 100];
+    assert_eq!(params(10.0, false).apply(&noisy, 8, 1, 255), noisy);
+    assert_ne!(params(0.0, false).apply(&noisy, 8, 1, 255), noisy);
+}
+
+#[test]
+fn luminance_mode_preserves_channel_differences() {
+    let img = ColorImage {
+        width: 8,
+        height: 1,
+        maxval: 255,
+        rpixels: step(),
+        gpixels: step().iter().map(|v| v + 20).collect(),
+        bpixels: step().iter().map(|v| v + 40).collect(),
+    };
+
+    let sharp = img.unsharp_mask(&params(0.0, true));
+    for i in 0..8 {
+        assert_eq!(sharp.gpixels[i] - sharp.rpixels[i], 20);
+        assert_eq!(sharp.bpixels[i] - sharp.rpixels[i], 40);
+    }
+    assert!(sharp.rpixels[3] < 50);
+}
@@ -1,12 +1,31 @@
 mod args;
 mod image;
 
-use args::ProgOpts;
+use args::{OutputMode, ProgOpts};
+use image::Image;
 
-fn main() -> Result<(), ()> {
-    let _ = ProgOpts::from_env();
+fn main() -> Result<(), String> {
+    let opts = ProgOpts::from_env()?;
 
-    Ok(())
-}
+    let img = Image::load(&opts.infile)
+        .map_err(|e| format!("{}: {}", opts.infile, e))?;
+    let out = img.manipulate(&opts.op)
+        .map_err(|e| e.to_string())?;
 
+    match opts.mode {
+        OutputMode::Stats(format) => {
+            let hist = out.histogram(format.bins);
+            print!("{}", if format.json { hist.to_json() } else { hist.to_text() });
+        }
+        OutputMode::Blobs(format) => {
+            let report = out.blobs(format.connectivity);
+            print!("{}", if format.json { report.to_json() } else { report.to_text() });
+        }
+        _ => {
+            out.save(&opts.outfile, &opts.mode)
+                .map_err(|e| format!("{}: {}", opts.outfile, e))?;
+        }
+    }
 
+    Ok(())
+}